@@ -0,0 +1,347 @@
+//! This module provides message catalogs for localizing diagnostics.
+//!
+//! Diagnostics can be defined as a `Message`: an ID, named arguments and an English fallback.
+//! Messages are resolved through a `Catalog`, such as a `FluentCatalog` loaded from a Fluent-like
+//! file. Messages missing from the catalog fall back to English.
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    error::{Error, Report},
+    loader::FileLoader,
+    source::Source,
+    span::Span,
+};
+
+/// The IDs and English text of the built-in labels used when rendering diagnostics.
+///
+/// Catalogs can translate these like any other message. The arrows default to the glyphs of the theme.
+pub const BUILTIN_MESSAGES: &[(&str, &str)] = &[
+    ("severity-error", "error"),
+    ("severity-warning", "warning"),
+    ("severity-info", "info"),
+    ("severity-note", "note"),
+    ("severity-help", "help"),
+    ("label-note", "note"),
+    ("label-help", "help"),
+    ("label-caused-by", "caused by"),
+    ("location-arrow", "-->"),
+    ("expansion-arrow", ":::"),
+    ("expansion-macro", "in this macro invocation"),
+    ("expansion-include", "in this include"),
+    (
+        "expansion-origin",
+        "this error originates in the macro `{ $name }`",
+    ),
+    (
+        "summary-errors-one",
+        "aborting due to { $count } previous error",
+    ),
+    (
+        "summary-errors-other",
+        "aborting due to { $count } previous errors",
+    ),
+    ("summary-warnings-one", "{ $count } warning emitted"),
+    ("summary-warnings-other", "{ $count } warnings emitted"),
+    ("summary-both", "{ $errors }; { $warnings }"),
+    (
+        "summary-dropped-one",
+        "{ $count } more error not shown because the error limit was reached",
+    ),
+    (
+        "summary-dropped-other",
+        "{ $count } more errors not shown because the error limit was reached",
+    ),
+];
+
+/// Defines the interface for looking up translated messages.
+pub trait Catalog: fmt::Debug + Send + Sync {
+    /// Returns the translation of the message with the given ID, if any.
+    ///
+    /// The translation may contain placeholders such as `{ $name }`.
+    fn get(&self, id: &str) -> Option<&str>;
+}
+
+/// A localizable message, identified by an ID and carrying named arguments.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::catalog::{FluentCatalog, Message};
+///
+/// let message = Message::new("unused-variable", "unused variable `{ $name }`").arg("name", "x");
+/// assert_eq!(message.resolve(None), "unused variable `x`");
+///
+/// let catalog = FluentCatalog::from_entries([("unused-variable", "variable inutilisée `{ $name }`")]);
+/// assert_eq!(message.resolve(Some(&catalog)), "variable inutilisée `x`");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// The ID of the message.
+    pub id: String,
+    /// The English text of the message, used when the catalog has no translation.
+    pub fallback: String,
+    /// The named arguments substituted into the message.
+    pub args: Vec<(String, String)>,
+}
+
+impl Message {
+    /// Creates a new `Message` with the given ID and English fallback.
+    pub fn new(id: &str, fallback: &str) -> Self {
+        Message {
+            id: id.to_string(),
+            fallback: fallback.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Creates a new `Message`, adding the given named argument.
+    pub fn arg(mut self, name: &str, value: impl fmt::Display) -> Self {
+        self.args.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Resolves the message through the given catalog, falling back to English.
+    pub fn resolve(&self, catalog: Option<&dyn Catalog>) -> String {
+        let template = catalog
+            .and_then(|catalog| catalog.get(&self.id))
+            .unwrap_or(&self.fallback);
+        interpolate(template, &self.args)
+    }
+}
+
+/// Returns the English text of the built-in message with the given ID.
+pub(crate) fn builtin_fallback(id: &str) -> &'static str {
+    BUILTIN_MESSAGES
+        .iter()
+        .find(|(builtin, _)| *builtin == id)
+        .map(|(_, text)| *text)
+        .expect("unknown built-in message")
+}
+
+/// Substitutes the named arguments into the `{ $name }` placeholders of the template.
+///
+/// Placeholders without a matching argument are kept as they are.
+fn interpolate(template: &str, args: &[(String, String)]) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output += &rest[..start];
+
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeholder = &rest[start..=end];
+        let value = placeholder[1..placeholder.len() - 1]
+            .trim()
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| arg == name))
+            .map(|(_, value)| value.as_str());
+        output += value.unwrap_or(placeholder);
+        rest = &rest[end + 1..];
+    }
+
+    output += rest;
+    output
+}
+
+/// A `Catalog` read from a Fluent-like file.
+///
+/// Each message is written as `id = text`, with `{ $name }` placeholders for arguments.
+/// Indented lines continue the previous message, and lines starting with `#` are comments.
+///
+/// ```text
+/// # French translations
+/// severity-error = erreur
+/// unused-variable = variable inutilisée `{ $name }`
+///     renommez-la si elle est intentionnelle
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FluentCatalog {
+    /// The translations, by message ID.
+    messages: HashMap<String, String>,
+}
+
+impl FluentCatalog {
+    /// Creates a new, empty `FluentCatalog`.
+    pub fn new() -> Self {
+        FluentCatalog {
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Creates a new `FluentCatalog` from the given IDs and translations.
+    pub fn from_entries<'e>(entries: impl IntoIterator<Item = (&'e str, &'e str)>) -> Self {
+        FluentCatalog {
+            messages: entries
+                .into_iter()
+                .map(|(id, text)| (id.to_string(), text.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Parses a catalog from the given source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error pointing at the first line that is neither a message, a continuation,
+    /// a comment nor blank.
+    pub fn parse<'a>(source: &'a Source<'a>) -> Result<Self, Error<'a>> {
+        let mut catalog = FluentCatalog::new();
+        let mut current: Option<String> = None;
+        let mut offset = 0;
+
+        for line in source.code.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with([' ', '\t']) {
+                let Some(id) = &current else {
+                    return Err(Error::new(
+                        "continuation line without a message".to_string(),
+                        source,
+                        Span::new(start, start + line.len()),
+                    ));
+                };
+                let text = catalog
+                    .messages
+                    .get_mut(id)
+                    .expect("current message exists");
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(line.trim());
+                continue;
+            }
+
+            let Some((id, text)) = line.split_once('=') else {
+                return Err(Error::new(
+                    "expected `=` after message ID".to_string(),
+                    source,
+                    Span::new(start, start + line.len()),
+                ));
+            };
+
+            let id = id.trim();
+            let valid = id.starts_with(|c: char| c.is_ascii_alphabetic())
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(Error::new(
+                    format!("invalid message ID `{}`", id),
+                    source,
+                    Span::new(start, start + line.find('=').unwrap_or(0)),
+                ));
+            }
+
+            catalog
+                .messages
+                .insert(id.to_string(), text.trim().to_string());
+            current = Some(id.to_string());
+        }
+
+        Ok(catalog)
+    }
+
+    /// Loads and parses the catalog at the given path using `loader`.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{
+    ///     catalog::{Catalog, FluentCatalog},
+    ///     loader::MemoryLoader,
+    /// };
+    ///
+    /// let mut loader = MemoryLoader::new();
+    /// loader.insert("fr.ftl", "severity-error = erreur\n");
+    ///
+    /// let catalog = FluentCatalog::load(&loader, "fr.ftl").unwrap();
+    /// assert_eq!(catalog.get("severity-error"), Some("erreur"));
+    /// ```
+    pub fn load(loader: &impl FileLoader, path: &str) -> Result<Self, Report> {
+        let source = Source::load(loader, path)?;
+        Ok(FluentCatalog::parse(&source)?)
+    }
+
+    /// Adds or replaces the translation of the message with the given ID.
+    pub fn insert(&mut self, id: &str, text: &str) {
+        self.messages.insert(id.to_string(), text.to_string());
+    }
+
+    /// Returns the number of translated messages.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if the catalog has no translations.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl Catalog for FluentCatalog {
+    fn get(&self, id: &str) -> Option<&str> {
+        self.messages.get(id).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::HasSpan;
+
+    #[test]
+    fn test_interpolate() {
+        let args = vec![("name".to_string(), "x".to_string())];
+        assert_eq!(interpolate("unused `{ $name }`", &args), "unused `x`");
+        assert_eq!(interpolate("unused `{$name}`", &args), "unused `x`");
+        assert_eq!(interpolate("{ $other } {", &args), "{ $other } {");
+    }
+
+    #[test]
+    fn test_fluent_catalog_parse() {
+        let source = Source::from_str(
+            "fr.ftl",
+            "# French\nseverity-error = erreur\r\n\nunused = variable inutilisée\n    renommez-la\n",
+        );
+        let catalog = FluentCatalog::parse(&source).unwrap();
+
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog.get("severity-error"), Some("erreur"));
+        assert_eq!(
+            catalog.get("unused"),
+            Some("variable inutilisée\nrenommez-la")
+        );
+    }
+
+    #[test]
+    fn test_fluent_catalog_parse_errors() {
+        let source = Source::from_str("fr.ftl", "ok = oui\nnot a message\n");
+        let error = FluentCatalog::parse(&source).unwrap_err();
+        assert_eq!(error.message(), "expected `=` after message ID");
+        assert_eq!(error.span(), Span::new(9, 22));
+
+        let source = Source::from_str("fr.ftl", "  orphan\n");
+        assert!(FluentCatalog::parse(&source).is_err());
+
+        let source = Source::from_str("fr.ftl", "1d = un\n");
+        assert!(FluentCatalog::parse(&source).is_err());
+    }
+
+    #[test]
+    fn test_message_fallback() {
+        let message = Message::new("missing", "{ $count } items").arg("count", 3);
+        let catalog = FluentCatalog::new();
+        assert_eq!(message.resolve(Some(&catalog)), "3 items");
+    }
+}
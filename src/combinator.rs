@@ -0,0 +1,484 @@
+//! This module provides parser combinators over a `TokenStream`.
+//!
+//! Parsers are plain values implementing the `Parser` trait, including closures, and are combined
+//! with a small set of functions: `token`, `seq`, `choice`, `many`, `sep_by`, `optional`,
+//! `delimited`, `map`, `label` and `cut`.
+//!
+//! Failed alternatives backtrack. When every alternative fails, the error that got the furthest is
+//! kept, and errors at the same position merge their expected sets, including what repetitions and
+//! optional parsers that stopped there expected. `cut` commits to a branch, so its errors are
+//! reported instead of backtracking. `run` reports the furthest failure of the whole parse, so an
+//! error deep inside a backtracked repetition is not hidden behind where the repetition stopped.
+
+use crate::{
+    error::Error,
+    parser::{Checkpoint, Failure, TokenStream},
+    source::Source,
+    span::Span,
+    token::{Token, TokenKind, describe_alternatives},
+};
+
+/// An error produced by a combinator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The span of the offending token, or the end-of-file span.
+    pub span: Span,
+    /// The descriptions of what was expected instead.
+    pub expected: Vec<String>,
+    /// The description of what was found.
+    pub found: String,
+    /// Whether the error is committed, and so must not be backtracked.
+    pub committed: bool,
+}
+
+impl ParseError {
+    /// Creates an error at the next token of the stream, expecting the given items.
+    ///
+    /// What parsers that were backtracked at the same position expected is included as well.
+    pub fn expected<T: TokenKind>(stream: &TokenStream<'_, T>, expected: Vec<String>) -> Self {
+        let mut hint = stream.hint();
+        hint.extend(expected);
+
+        ParseError {
+            span: stream.current_span(),
+            expected: hint,
+            found: stream
+                .peek()
+                .map_or_else(|| "end of file".to_string(), |token| token.kind.describe()),
+            committed: false,
+        }
+    }
+
+    /// Merges two errors of alternatives, keeping the one that got the furthest
+    /// and merging the expected sets of errors at the same position.
+    pub fn merge(mut self, other: ParseError) -> ParseError {
+        match self.span.start.cmp(&other.span.start) {
+            std::cmp::Ordering::Less => other,
+            std::cmp::Ordering::Greater => self,
+            std::cmp::Ordering::Equal => {
+                self.expected.extend(other.expected);
+                self.committed |= other.committed;
+                self
+            }
+        }
+    }
+
+    /// Converts the error into a runic `Error`, such as "expected `;` or `}`, found `)`".
+    pub fn into_error<'a>(self, source: &'a Source<'a>) -> Error<'a> {
+        Error::new(
+            format!(
+                "expected {}, found {}",
+                describe_alternatives(self.expected),
+                self.found
+            ),
+            source,
+            self.span,
+        )
+    }
+}
+
+impl From<Failure> for ParseError {
+    fn from(failure: Failure) -> Self {
+        ParseError {
+            span: failure.span,
+            expected: failure.expected,
+            found: failure.found,
+            committed: false,
+        }
+    }
+}
+
+impl From<ParseError> for Failure {
+    fn from(error: ParseError) -> Self {
+        Failure {
+            span: error.span,
+            expected: error.expected,
+            found: error.found,
+        }
+    }
+}
+
+/// The result of a combinator.
+pub type ParseResult<O> = Result<O, ParseError>;
+
+/// Defines the interface for parsers.
+///
+/// Closures taking a `TokenStream` and returning a `ParseResult` are parsers.
+pub trait Parser<'a, T, O> {
+    /// Parses a value from the stream.
+    fn parse(&self, stream: &mut TokenStream<'a, T>) -> ParseResult<O>;
+}
+
+impl<'a, T, O, F> Parser<'a, T, O> for F
+where
+    F: Fn(&mut TokenStream<'a, T>) -> ParseResult<O>,
+{
+    fn parse(&self, stream: &mut TokenStream<'a, T>) -> ParseResult<O> {
+        self(stream)
+    }
+}
+
+/// Runs the parser over the whole stream, converting errors into runic `Error`s.
+///
+/// When the parser fails, or stops before the end of the stream, the error that got the furthest
+/// is reported, including the failures of backtracked parsers since the start of the run.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     combinator::{delimited, run, sep_by, token},
+///     parser::TokenStream,
+///     source::Source,
+///     span::Span,
+///     token::Token,
+/// };
+///
+/// let source = Source::from_str("main.rn", "(x, x]");
+/// let tokens = vec![
+///     Token::new("(", Span::new(0, 1)),
+///     Token::new("x", Span::new(1, 2)),
+///     Token::new(",", Span::new(2, 3)),
+///     Token::new("x", Span::new(4, 5)),
+///     Token::new("]", Span::new(5, 6)),
+/// ];
+///
+/// let list = delimited(token("("), sep_by(token("x"), token(",")), token(")"));
+/// let error = run(&list, &mut TokenStream::new(&source, tokens)).unwrap_err();
+/// assert_eq!(error.message(), "expected `,` or `)`, found `]`");
+/// ```
+pub fn run<'a, T: TokenKind, O>(
+    parser: &impl Parser<'a, T, O>,
+    stream: &mut TokenStream<'a, T>,
+) -> Result<O, Error<'a>> {
+    let source = stream.source();
+    stream.clear_failures();
+    let error = match parser.parse(stream) {
+        Ok(output) if stream.is_at_end() => return Ok(output),
+        Ok(_) => ParseError::expected(stream, vec!["end of file".to_string()]),
+        Err(error) => error,
+    };
+
+    let error = match stream.furthest_failure() {
+        Some(furthest) => error.merge(furthest.clone().into()),
+        None => error,
+    };
+    Err(error.into_error(source))
+}
+
+/// Parses a token of the same kind as `kind`, ignoring payloads.
+pub fn token<'a, T: TokenKind + Clone>(kind: T) -> impl Parser<'a, T, Token<T>> {
+    move |stream: &mut TokenStream<'a, T>| match stream.eat_kind(&kind) {
+        Some(token) => Ok(token.clone()),
+        None => Err(ParseError::expected(stream, vec![kind.describe()])),
+    }
+}
+
+/// Parses the parsers of a tuple one after the other, returning a tuple of their outputs.
+pub fn seq<P>(parsers: P) -> Seq<P> {
+    Seq(parsers)
+}
+
+/// Tries the parsers of a tuple in order, returning the output of the first one that succeeds.
+pub fn choice<P>(parsers: P) -> Choice<P> {
+    Choice(parsers)
+}
+
+/// The parser returned by `seq`.
+#[derive(Debug, Clone, Copy)]
+pub struct Seq<P>(P);
+
+/// The parser returned by `choice`.
+#[derive(Debug, Clone, Copy)]
+pub struct Choice<P>(P);
+
+/// Implements `Parser` for `Seq` and `Choice` over tuples of parsers.
+macro_rules! impl_tuples {
+    ($($parser:ident $output:ident),+) => {
+        impl<'a, T, $($parser, $output),+> Parser<'a, T, ($($output,)+)> for Seq<($($parser,)+)>
+        where
+            $($parser: Parser<'a, T, $output>),+
+        {
+            #[allow(non_snake_case)]
+            fn parse(&self, stream: &mut TokenStream<'a, T>) -> ParseResult<($($output,)+)> {
+                let ($($parser,)+) = &self.0;
+                Ok(($($parser.parse(stream)?,)+))
+            }
+        }
+
+        impl<'a, T, O, $($parser),+> Parser<'a, T, O> for Choice<($($parser,)+)>
+        where
+            $($parser: Parser<'a, T, O>),+
+        {
+            #[allow(non_snake_case)]
+            fn parse(&self, stream: &mut TokenStream<'a, T>) -> ParseResult<O> {
+                let ($($parser,)+) = &self.0;
+                let start = stream.mark();
+                let mut error: Option<ParseError> = None;
+
+                $(
+                    match $parser.parse(stream) {
+                        Ok(output) => {
+                            if let Some(error) = &error {
+                                stream.record_failure(error.clone().into());
+                            }
+                            return Ok(output);
+                        }
+                        Err(failure) if failure.committed => return Err(failure),
+                        Err(failure) => {
+                            error = Some(match error {
+                                Some(error) => error.merge(failure),
+                                None => failure,
+                            });
+                            stream.reset(start);
+                        }
+                    }
+                )+
+
+                Err(error.expect("choice has at least one parser"))
+            }
+        }
+    };
+}
+
+impl_tuples!(A OA, B OB);
+impl_tuples!(A OA, B OB, C OC);
+impl_tuples!(A OA, B OB, C OC, D OD);
+impl_tuples!(A OA, B OB, C OC, D OD, E OE);
+impl_tuples!(A OA, B OB, C OC, D OD, E OE, F OF);
+
+/// Returns to the checkpoint after a failed parser, remembering its failure.
+pub(crate) fn backtrack<T>(stream: &mut TokenStream<'_, T>, start: Checkpoint, error: ParseError) {
+    stream.reset(start);
+    stream.record_failure(error.into());
+}
+
+/// Parses the parser zero or more times, until it fails.
+///
+/// Committed errors are returned; other errors stop the repetition and are backtracked.
+pub fn many<'a, T, O>(parser: impl Parser<'a, T, O>) -> impl Parser<'a, T, Vec<O>> {
+    move |stream: &mut TokenStream<'a, T>| {
+        let mut outputs = Vec::new();
+        loop {
+            let start = stream.mark();
+            match parser.parse(stream) {
+                Ok(output) => {
+                    outputs.push(output);
+                    if stream.mark() == start {
+                        return Ok(outputs);
+                    }
+                }
+                Err(error) if error.committed => return Err(error),
+                Err(error) => {
+                    backtrack(stream, start, error);
+                    return Ok(outputs);
+                }
+            }
+        }
+    }
+}
+
+/// Parses zero or more occurrences of the parser, separated by the separator.
+///
+/// An item is required after each separator.
+pub fn sep_by<'a, T, O, S>(
+    parser: impl Parser<'a, T, O>,
+    separator: impl Parser<'a, T, S>,
+) -> impl Parser<'a, T, Vec<O>> {
+    move |stream: &mut TokenStream<'a, T>| {
+        let mut outputs = Vec::new();
+
+        let start = stream.mark();
+        match parser.parse(stream) {
+            Ok(output) => outputs.push(output),
+            Err(error) if error.committed => return Err(error),
+            Err(error) => {
+                backtrack(stream, start, error);
+                return Ok(outputs);
+            }
+        }
+
+        loop {
+            let start = stream.mark();
+            match separator.parse(stream) {
+                Ok(_) => outputs.push(parser.parse(stream)?),
+                Err(error) if error.committed => return Err(error),
+                Err(error) => {
+                    backtrack(stream, start, error);
+                    return Ok(outputs);
+                }
+            }
+        }
+    }
+}
+
+/// Parses the parser if possible, backtracking and returning `None` if it fails without committing.
+pub fn optional<'a, T, O>(parser: impl Parser<'a, T, O>) -> impl Parser<'a, T, Option<O>> {
+    move |stream: &mut TokenStream<'a, T>| {
+        let start = stream.mark();
+        match parser.parse(stream) {
+            Ok(output) => Ok(Some(output)),
+            Err(error) if error.committed => Err(error),
+            Err(error) => {
+                backtrack(stream, start, error);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Parses `open`, the parser and `close`, returning the output of the parser.
+pub fn delimited<'a, T, L, O, R>(
+    open: impl Parser<'a, T, L>,
+    parser: impl Parser<'a, T, O>,
+    close: impl Parser<'a, T, R>,
+) -> impl Parser<'a, T, O> {
+    move |stream: &mut TokenStream<'a, T>| {
+        open.parse(stream)?;
+        let output = parser.parse(stream)?;
+        close.parse(stream)?;
+        Ok(output)
+    }
+}
+
+/// Transforms the output of the parser.
+pub fn map<'a, T, O, U>(
+    parser: impl Parser<'a, T, O>,
+    f: impl Fn(O) -> U,
+) -> impl Parser<'a, T, U> {
+    move |stream: &mut TokenStream<'a, T>| parser.parse(stream).map(&f)
+}
+
+/// Names what the parser expects, such as `expression`, when it fails without consuming anything.
+pub fn label<'a, T, O>(parser: impl Parser<'a, T, O>, name: &str) -> impl Parser<'a, T, O> {
+    let name = name.to_string();
+    move |stream: &mut TokenStream<'a, T>| {
+        let start = stream.current_span();
+        parser.parse(stream).map_err(|mut error| {
+            if error.span.start == start.start && !error.committed {
+                error.expected = vec![name.clone()];
+            }
+            error
+        })
+    }
+}
+
+/// Commits to the parser, so that its errors are reported instead of backtracked.
+pub fn cut<'a, T, O>(parser: impl Parser<'a, T, O>) -> impl Parser<'a, T, O> {
+    move |stream: &mut TokenStream<'a, T>| {
+        parser.parse(stream).map_err(|mut error| {
+            error.committed = true;
+            error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        span::HasSpan,
+        test_utils::{parse_words, token_stream},
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Statement {
+        Let(String),
+        Print(Vec<String>),
+    }
+
+    fn statement<'a>() -> impl Parser<'a, &'a str, Statement> {
+        let name = label(
+            map(choice((token("x"), token("y"))), |token: Token<&str>| {
+                token.kind.to_string()
+            }),
+            "name",
+        );
+        let let_statement = map(seq((token("let"), cut(name))), |(_, name)| {
+            Statement::Let(name)
+        });
+        let print_statement = map(
+            seq((
+                token("print"),
+                delimited(token("("), sep_by(token("x"), token(",")), token(")")),
+            )),
+            |(_, args)| Statement::Print(args.iter().map(|a| a.kind.to_string()).collect()),
+        );
+        map(
+            seq((
+                choice((let_statement, print_statement)),
+                optional(token(";")),
+            )),
+            |(statement, _)| statement,
+        )
+    }
+
+    fn parse(code: &str) -> Result<Vec<Statement>, (String, Span)> {
+        parse_words(code, |stream| run(&many(statement()), stream))
+    }
+
+    #[test]
+    fn test_combinators() {
+        assert_eq!(
+            parse("let x ; print ( x , x ) let y").unwrap(),
+            vec![
+                Statement::Let("x".to_string()),
+                Statement::Print(vec!["x".to_string(), "x".to_string()]),
+                Statement::Let("y".to_string()),
+            ]
+        );
+        assert_eq!(parse("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_combinator_errors() {
+        assert_eq!(
+            parse("let 1").unwrap_err(),
+            ("expected name, found `1`".to_string(), Span::new(4, 5))
+        );
+        assert_eq!(
+            parse("print ( x x )").unwrap_err(),
+            (
+                "expected `,` or `)`, found `x`".to_string(),
+                Span::new(10, 11)
+            )
+        );
+
+        let source = Source::from_str("test.rn", "print ( x x )");
+        let mut stream = token_stream(&source);
+        let error = statement().parse(&mut stream).unwrap_err();
+        assert_eq!(
+            error.into_error(&source).message(),
+            "expected `,` or `)`, found `x`"
+        );
+    }
+
+    #[test]
+    fn test_run_forgets_earlier_failures() {
+        let source = Source::from_str("test.rn", "print ( x x )");
+        let mut stream = token_stream(&source);
+        let start = stream.mark();
+        assert!(run(&many(statement()), &mut stream).is_err());
+
+        stream.reset(start);
+        let error = run(&token("let"), &mut stream).unwrap_err();
+        assert_eq!(error.message(), "expected `let`, found `print`");
+        assert_eq!(error.span(), Span::new(0, 5));
+    }
+
+    #[test]
+    fn test_parse_error_merge() {
+        let error = |start: usize, expected: &str| ParseError {
+            span: Span::empty(start),
+            expected: vec![expected.to_string()],
+            found: "`x`".to_string(),
+            committed: false,
+        };
+
+        assert_eq!(error(1, "a").merge(error(3, "b")), error(3, "b"));
+        assert_eq!(
+            error(1, "a").merge(error(1, "b")).expected,
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
+}
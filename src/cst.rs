@@ -0,0 +1,920 @@
+//! This module provides a lossless concrete syntax tree, split into green and red trees.
+//!
+//! Green nodes are immutable, reference-counted and know only their kind, length and children,
+//! so unchanged subtrees can be shared between versions of a file. Red nodes are cursors built
+//! on demand on top of them, adding parent pointers and absolute spans. Every token, including
+//! whitespace and comments, is kept in the tree, so its text is exactly the source code.
+
+use std::{fmt, iter, rc::Rc, sync::Arc};
+
+use crate::{
+    source::Source,
+    span::{HasSpan, Span},
+    token::Token,
+};
+
+/// An immutable token of a green tree, holding its kind and text.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct GreenToken<K>(Arc<GreenTokenData<K>>);
+
+/// The shared data of a `GreenToken`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct GreenTokenData<K> {
+    /// The kind of the token.
+    kind: K,
+    /// The text of the token.
+    text: Box<str>,
+}
+
+impl<K> GreenToken<K> {
+    /// Creates a new `GreenToken`.
+    pub fn new(kind: K, text: &str) -> Self {
+        GreenToken(Arc::new(GreenTokenData {
+            kind,
+            text: text.into(),
+        }))
+    }
+
+    /// Returns the kind of the token.
+    pub fn kind(&self) -> &K {
+        &self.0.kind
+    }
+
+    /// Returns the text of the token.
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    /// Returns the length of the token in bytes.
+    pub fn len(&self) -> usize {
+        self.0.text.len()
+    }
+
+    /// Returns `true` if the token has no text.
+    pub fn is_empty(&self) -> bool {
+        self.0.text.is_empty()
+    }
+}
+
+impl<K> Clone for GreenToken<K> {
+    fn clone(&self) -> Self {
+        GreenToken(Arc::clone(&self.0))
+    }
+}
+
+/// An immutable node of a green tree, holding its kind and children.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct GreenNode<K>(Arc<GreenNodeData<K>>);
+
+/// The shared data of a `GreenNode`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct GreenNodeData<K> {
+    /// The kind of the node.
+    kind: K,
+    /// The length of the node in bytes, which is the sum of the lengths of its children.
+    len: usize,
+    /// The children of the node.
+    children: Vec<GreenElement<K>>,
+}
+
+impl<K> GreenNode<K> {
+    /// Creates a new `GreenNode`.
+    pub fn new(kind: K, children: Vec<GreenElement<K>>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        GreenNode(Arc::new(GreenNodeData {
+            kind,
+            len,
+            children,
+        }))
+    }
+
+    /// Returns the kind of the node.
+    pub fn kind(&self) -> &K {
+        &self.0.kind
+    }
+
+    /// Returns the length of the node in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    /// Returns `true` if the node has no text.
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    /// Returns the children of the node.
+    pub fn children(&self) -> &[GreenElement<K>] {
+        &self.0.children
+    }
+
+    /// Returns `true` if both nodes are the same shared node, not only equal ones.
+    pub fn ptr_eq(&self, other: &GreenNode<K>) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<K> Clone for GreenNode<K> {
+    fn clone(&self) -> Self {
+        GreenNode(Arc::clone(&self.0))
+    }
+}
+
+impl<K: Clone> GreenNode<K> {
+    /// Returns a copy of the node with the child at `index` replaced.
+    ///
+    /// The other children are shared with the original node, not copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replace_child(&self, index: usize, child: GreenElement<K>) -> GreenNode<K> {
+        let mut children = self.0.children.clone();
+        children[index] = child;
+        GreenNode::new(self.0.kind.clone(), children)
+    }
+}
+
+impl<K> fmt::Display for GreenNode<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in self.children() {
+            match child {
+                GreenElement::Node(node) => write!(f, "{}", node)?,
+                GreenElement::Token(token) => write!(f, "{}", token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A child of a green node, either a node or a token.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement<K> {
+    /// A child node.
+    Node(GreenNode<K>),
+    /// A child token.
+    Token(GreenToken<K>),
+}
+
+impl<K> GreenElement<K> {
+    /// Returns the kind of the element.
+    pub fn kind(&self) -> &K {
+        match self {
+            GreenElement::Node(node) => node.kind(),
+            GreenElement::Token(token) => token.kind(),
+        }
+    }
+
+    /// Returns the length of the element in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len(),
+            GreenElement::Token(token) => token.len(),
+        }
+    }
+
+    /// Returns `true` if the element has no text.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K> From<GreenNode<K>> for GreenElement<K> {
+    fn from(node: GreenNode<K>) -> Self {
+        GreenElement::Node(node)
+    }
+}
+
+impl<K> From<GreenToken<K>> for GreenElement<K> {
+    fn from(token: GreenToken<K>) -> Self {
+        GreenElement::Token(token)
+    }
+}
+
+/// A saved position of a `GreenNodeBuilder`, used to wrap already built children in a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BuilderCheckpoint(usize);
+
+/// Builds a green tree from the events of a parser.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{cst::{GreenNodeBuilder, SyntaxNode}, source::Source, span::Span, token::Token};
+///
+/// let source = Source::from_str("main.rn", "1 + 2");
+/// let tokens = [("int", 0, 1), ("ws", 1, 2), ("+", 2, 3), ("ws", 3, 4), ("int", 4, 5)]
+///     .map(|(kind, start, end)| Token::new(kind, Span::new(start, end)));
+///
+/// let mut builder = GreenNodeBuilder::new();
+/// builder.start_node("root");
+/// let checkpoint = builder.checkpoint();
+/// builder.push(&tokens[0], &source);
+/// builder.start_node_at(checkpoint, "binary");
+/// for token in &tokens[1..] {
+///     builder.push(token, &source);
+/// }
+/// builder.finish_node();
+/// builder.finish_node();
+///
+/// let root = SyntaxNode::new_root(builder.finish());
+/// let binary = root.first_child().unwrap();
+/// assert_eq!(*binary.kind(), "binary");
+/// assert_eq!(binary.to_string(), "1 + 2");
+/// ```
+#[derive(Debug)]
+pub struct GreenNodeBuilder<K> {
+    /// The kinds of the open nodes, with the index of their first child in `children`.
+    parents: Vec<(K, usize)>,
+    /// The children of the open nodes, from the outermost to the innermost.
+    children: Vec<GreenElement<K>>,
+}
+
+impl<K> GreenNodeBuilder<K> {
+    /// Creates a new, empty `GreenNodeBuilder`.
+    pub fn new() -> Self {
+        GreenNodeBuilder {
+            parents: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Opens a new node of the given kind. Following tokens and nodes become its children.
+    pub fn start_node(&mut self, kind: K) {
+        self.parents.push((kind, self.children.len()));
+    }
+
+    /// Adds a token with the given kind and text to the current node.
+    pub fn token(&mut self, kind: K, text: &str) {
+        self.children.push(GreenToken::new(kind, text).into());
+    }
+
+    /// Closes the current node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no open node.
+    pub fn finish_node(&mut self) {
+        let (kind, first) = self.parents.pop().expect("no node to finish");
+        let children = self.children.split_off(first);
+        self.children.push(GreenNode::new(kind, children).into());
+    }
+
+    /// Saves the current position, to open a node there later with `start_node_at`.
+    pub fn checkpoint(&self) -> BuilderCheckpoint {
+        BuilderCheckpoint(self.children.len())
+    }
+
+    /// Opens a new node at the checkpoint, making the children added since then its children.
+    ///
+    /// This is useful for constructs only recognized after their first child, such as binary
+    /// expressions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint is before the start of the current node, or after its last child.
+    pub fn start_node_at(&mut self, checkpoint: BuilderCheckpoint, kind: K) {
+        let BuilderCheckpoint(first) = checkpoint;
+        assert!(
+            first <= self.children.len(),
+            "checkpoint is after the last child"
+        );
+        if let Some((_, start)) = self.parents.last() {
+            assert!(
+                *start <= first,
+                "checkpoint is before the start of the current node"
+            );
+        }
+        self.parents.push((kind, first));
+    }
+
+    /// Returns the root node of the built tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node is still open, or if the tree does not have exactly one root node.
+    pub fn finish(mut self) -> GreenNode<K> {
+        assert!(self.parents.is_empty(), "a node is still open");
+        match (self.children.pop(), self.children.is_empty()) {
+            (Some(GreenElement::Node(root)), true) => root,
+            _ => panic!("the tree must have exactly one root node"),
+        }
+    }
+}
+
+impl<K: Clone> GreenNodeBuilder<K> {
+    /// Adds a token produced by a lexer, taking its text from the source, to the current node.
+    ///
+    /// For the tree to be lossless, trivia such as whitespace and comments must be pushed
+    /// as tokens too, instead of being skipped by the lexer.
+    pub fn push(&mut self, token: &Token<K>, source: &Source) {
+        self.token(token.kind.clone(), token.text(source));
+    }
+}
+
+impl<K> Default for GreenNodeBuilder<K> {
+    fn default() -> Self {
+        GreenNodeBuilder::new()
+    }
+}
+
+/// A node of a red tree: a cursor over a green node, knowing its parent and absolute position.
+///
+/// Red nodes are created on demand while navigating, and are cheap to clone.
+pub struct SyntaxNode<K>(Rc<NodeData<K>>);
+
+/// The data of a `SyntaxNode`.
+struct NodeData<K> {
+    /// The green node of this node.
+    green: GreenNode<K>,
+    /// The parent of this node, unless it is the root.
+    parent: Option<SyntaxNode<K>>,
+    /// The index of this node among the children of its parent.
+    index: usize,
+    /// The absolute offset of this node in the source code.
+    offset: usize,
+}
+
+impl<K> SyntaxNode<K> {
+    /// Creates the root of a red tree over the given green node.
+    pub fn new_root(green: GreenNode<K>) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    /// Returns the kind of the node.
+    pub fn kind(&self) -> &K {
+        self.0.green.kind()
+    }
+
+    /// Returns the green node of this node.
+    pub fn green(&self) -> &GreenNode<K> {
+        &self.0.green
+    }
+
+    /// Returns the parent of the node, unless it is the root.
+    pub fn parent(&self) -> Option<SyntaxNode<K>> {
+        self.0.parent.clone()
+    }
+
+    /// Returns the node and its ancestors, from the innermost to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode<K>> + use<K> {
+        iter::successors(Some(self.clone()), |node| node.parent())
+    }
+
+    /// Returns the child nodes and tokens of the node, in order.
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement<K>> + use<K> {
+        let children = self.0.green.children().len();
+        let parent = self.clone();
+        let mut offset = self.0.offset;
+        (0..children).map(move |index| {
+            let element = parent.child_at(index, offset);
+            offset += parent.0.green.children()[index].len();
+            element
+        })
+    }
+
+    /// Returns the child nodes of the node, in order.
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode<K>> + use<K> {
+        self.children_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    /// Returns the first child node of the node.
+    pub fn first_child(&self) -> Option<SyntaxNode<K>> {
+        self.children().next()
+    }
+
+    /// Returns the last child node of the node.
+    pub fn last_child(&self) -> Option<SyntaxNode<K>> {
+        self.children().last()
+    }
+
+    /// Returns the next sibling node, skipping tokens.
+    pub fn next_sibling(&self) -> Option<SyntaxNode<K>> {
+        let parent = self.0.parent.as_ref()?;
+        let mut offset = self.0.offset + self.0.green.len();
+        for (index, child) in parent
+            .0
+            .green
+            .children()
+            .iter()
+            .enumerate()
+            .skip(self.0.index + 1)
+        {
+            if let GreenElement::Node(_) = child {
+                return parent.child_at(index, offset).into_node();
+            }
+            offset += child.len();
+        }
+        None
+    }
+
+    /// Returns the previous sibling node, skipping tokens.
+    pub fn prev_sibling(&self) -> Option<SyntaxNode<K>> {
+        let parent = self.0.parent.as_ref()?;
+        let mut offset = self.0.offset;
+        let children = &parent.0.green.children()[..self.0.index];
+        for (index, child) in children.iter().enumerate().rev() {
+            offset -= child.len();
+            if let GreenElement::Node(_) = child {
+                return parent.child_at(index, offset).into_node();
+            }
+        }
+        None
+    }
+
+    /// Returns the node and its descendant nodes, in preorder.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode<K>> + use<K> {
+        let root = self.clone();
+        iter::successors(Some(self.clone()), move |node| node.preorder_next(&root))
+    }
+
+    /// Returns the descendant tokens of the node, in source order.
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken<K>> + use<K> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens.into_iter()
+    }
+
+    /// Returns the first descendant token of the node.
+    pub fn first_token(&self) -> Option<SyntaxToken<K>> {
+        self.children_with_tokens().find_map(|child| match child {
+            SyntaxElement::Node(node) => node.first_token(),
+            SyntaxElement::Token(token) => Some(token),
+        })
+    }
+
+    /// Returns the last descendant token of the node.
+    pub fn last_token(&self) -> Option<SyntaxToken<K>> {
+        let children = self.children_with_tokens().collect::<Vec<_>>();
+        children.into_iter().rev().find_map(|child| match child {
+            SyntaxElement::Node(node) => node.last_token(),
+            SyntaxElement::Token(token) => Some(token),
+        })
+    }
+
+    /// Returns the descendant token containing the byte offset, if any.
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken<K>> {
+        self.children_with_tokens()
+            .find(|child| child.span().contains_offset(offset))
+            .and_then(|child| match child {
+                SyntaxElement::Node(node) => node.token_at_offset(offset),
+                SyntaxElement::Token(token) => Some(token),
+            })
+    }
+
+    /// Returns a new root green node, with this node replaced by `green`.
+    ///
+    /// Only the ancestors of this node are rebuilt; every other subtree is shared with the
+    /// original tree.
+    pub fn replace_with(&self, green: GreenNode<K>) -> GreenNode<K>
+    where
+        K: Clone,
+    {
+        match self.parent() {
+            Some(parent) => {
+                let new_parent = parent.green().replace_child(self.0.index, green.into());
+                parent.replace_with(new_parent)
+            }
+            None => green,
+        }
+    }
+
+    /// Returns the first child node that can be cast to the AST node type `N`.
+    pub fn child_as<N: AstNode<K>>(&self) -> Option<N> {
+        self.children().find_map(N::cast)
+    }
+
+    /// Returns the child nodes that can be cast to the AST node type `N`.
+    pub fn children_as<N: AstNode<K>>(&self) -> impl Iterator<Item = N> + use<K, N> {
+        self.children().filter_map(N::cast)
+    }
+
+    /// Creates the red element for the child at `index`, located at `offset`.
+    fn child_at(&self, index: usize, offset: usize) -> SyntaxElement<K> {
+        match &self.0.green.children()[index] {
+            GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                green: green.clone(),
+                parent: Some(self.clone()),
+                index,
+                offset,
+            }))),
+            GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                green: green.clone(),
+                parent: self.clone(),
+                index,
+                offset,
+            }),
+        }
+    }
+
+    /// Returns the node after this one in a preorder traversal of `root`.
+    fn preorder_next(&self, root: &SyntaxNode<K>) -> Option<SyntaxNode<K>> {
+        if let Some(child) = self.first_child() {
+            return Some(child);
+        }
+
+        let mut node = self.clone();
+        while node != *root {
+            if let Some(sibling) = node.next_sibling() {
+                return Some(sibling);
+            }
+            node = node.parent()?;
+        }
+        None
+    }
+
+    /// Pushes the descendant tokens of the node to `tokens`, in source order.
+    fn collect_tokens(&self, tokens: &mut Vec<SyntaxToken<K>>) {
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+}
+
+impl<K> HasSpan for SyntaxNode<K> {
+    fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.len())
+    }
+}
+
+impl<K> Clone for SyntaxNode<K> {
+    fn clone(&self) -> Self {
+        SyntaxNode(Rc::clone(&self.0))
+    }
+}
+
+impl<K> PartialEq for SyntaxNode<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.green.ptr_eq(&other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl<K> Eq for SyntaxNode<K> {}
+
+impl<K: fmt::Debug> fmt::Debug for SyntaxNode<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.kind(), span.start, span.end)
+    }
+}
+
+impl<K> fmt::Display for SyntaxNode<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.green)
+    }
+}
+
+/// A token of a red tree, knowing its parent and absolute position.
+pub struct SyntaxToken<K> {
+    /// The green token of this token.
+    green: GreenToken<K>,
+    /// The parent of this token.
+    parent: SyntaxNode<K>,
+    /// The index of this token among the children of its parent.
+    index: usize,
+    /// The absolute offset of this token in the source code.
+    offset: usize,
+}
+
+impl<K> SyntaxToken<K> {
+    /// Returns the kind of the token.
+    pub fn kind(&self) -> &K {
+        self.green.kind()
+    }
+
+    /// Returns the text of the token.
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    /// Returns the green token of this token.
+    pub fn green(&self) -> &GreenToken<K> {
+        &self.green
+    }
+
+    /// Returns the parent of the token.
+    pub fn parent(&self) -> SyntaxNode<K> {
+        self.parent.clone()
+    }
+
+    /// Returns the next sibling node or token.
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement<K>> {
+        let index = self.index + 1;
+        (index < self.parent.0.green.children().len())
+            .then(|| self.parent.child_at(index, self.offset + self.green.len()))
+    }
+
+    /// Returns the previous sibling node or token.
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement<K>> {
+        let index = self.index.checked_sub(1)?;
+        let offset = self.offset - self.parent.0.green.children()[index].len();
+        Some(self.parent.child_at(index, offset))
+    }
+}
+
+impl<K> Clone for SyntaxToken<K> {
+    fn clone(&self) -> Self {
+        SyntaxToken {
+            green: self.green.clone(),
+            parent: self.parent.clone(),
+            index: self.index,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<K> PartialEq for SyntaxToken<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parent == other.parent && self.index == other.index
+    }
+}
+
+impl<K> Eq for SyntaxToken<K> {}
+
+impl<K> HasSpan for SyntaxToken<K> {
+    fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.len())
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for SyntaxToken<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(
+            f,
+            "{:?}@{}..{} {:?}",
+            self.kind(),
+            span.start,
+            span.end,
+            self.text()
+        )
+    }
+}
+
+impl<K> fmt::Display for SyntaxToken<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// A child of a red node, either a node or a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement<K> {
+    /// A child node.
+    Node(SyntaxNode<K>),
+    /// A child token.
+    Token(SyntaxToken<K>),
+}
+
+impl<K> SyntaxElement<K> {
+    /// Returns the kind of the element.
+    pub fn kind(&self) -> &K {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+
+    /// Returns the node, if the element is one.
+    pub fn into_node(self) -> Option<SyntaxNode<K>> {
+        match self {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        }
+    }
+
+    /// Returns the token, if the element is one.
+    pub fn into_token(self) -> Option<SyntaxToken<K>> {
+        match self {
+            SyntaxElement::Node(_) => None,
+            SyntaxElement::Token(token) => Some(token),
+        }
+    }
+}
+
+impl<K> HasSpan for SyntaxElement<K> {
+    fn span(&self) -> Span {
+        match self {
+            SyntaxElement::Node(node) => node.span(),
+            SyntaxElement::Token(token) => token.span(),
+        }
+    }
+}
+
+/// A typed view over a `SyntaxNode` of a given kind, used to define an AST on top of the CST.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::cst::{AstNode, SyntaxNode};
+///
+/// struct Block(SyntaxNode<&'static str>);
+///
+/// impl AstNode<&'static str> for Block {
+///     fn can_cast(kind: &&'static str) -> bool {
+///         *kind == "block"
+///     }
+///
+///     fn cast(node: SyntaxNode<&'static str>) -> Option<Self> {
+///         Self::can_cast(node.kind()).then_some(Block(node))
+///     }
+///
+///     fn syntax(&self) -> &SyntaxNode<&'static str> {
+///         &self.0
+///     }
+/// }
+/// ```
+pub trait AstNode<K>: Sized {
+    /// Returns `true` if nodes of the given kind can be cast to this type.
+    fn can_cast(kind: &K) -> bool;
+
+    /// Casts the node to this type, if it is of a matching kind.
+    fn cast(node: SyntaxNode<K>) -> Option<Self>;
+
+    /// Returns the underlying syntax node.
+    fn syntax(&self) -> &SyntaxNode<K>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds `let x = 1 ;` as a `let` node with a nested `expr` node, keeping whitespace.
+    fn build_let() -> GreenNode<&'static str> {
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node("root");
+        builder.start_node("let");
+        builder.token("let", "let");
+        builder.token("ws", " ");
+        builder.token("ident", "x");
+        builder.token("ws", " ");
+        builder.token("=", "=");
+        builder.token("ws", " ");
+        builder.start_node("expr");
+        builder.token("int", "1");
+        builder.finish_node();
+        builder.token(";", ";");
+        builder.finish_node();
+        builder.token("ws", "\n");
+        builder.finish_node();
+        builder.finish()
+    }
+
+    #[test]
+    fn test_green_tree_is_lossless() {
+        let green = build_let();
+        assert_eq!(*green.kind(), "root");
+        assert_eq!(green.len(), 11);
+        assert_eq!(green.to_string(), "let x = 1;\n");
+
+        let source = Source::from_str("test.rn", "a b");
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node("root");
+        builder.push(&Token::new("ident", Span::new(0, 1)), &source);
+        builder.push(&Token::new("ws", Span::new(1, 2)), &source);
+        builder.push(&Token::new("ident", Span::new(2, 3)), &source);
+        builder.finish_node();
+        assert_eq!(builder.finish().to_string(), "a b");
+    }
+
+    #[test]
+    fn test_red_tree_navigation() {
+        let root = SyntaxNode::new_root(build_let());
+        let statement = root.first_child().unwrap();
+        let expr = statement.first_child().unwrap();
+
+        assert_eq!(*expr.kind(), "expr");
+        assert_eq!(expr.span(), Span::new(8, 9));
+        assert_eq!(expr.parent(), Some(statement.clone()));
+        assert_eq!(expr.ancestors().count(), 3);
+        assert_eq!(
+            root.descendants()
+                .map(|node| *node.kind())
+                .collect::<Vec<_>>(),
+            vec!["root", "let", "expr"]
+        );
+
+        let tokens = root.tokens().collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 9);
+        assert_eq!(tokens[2].text(), "x");
+        assert_eq!(tokens[2].span(), Span::new(4, 5));
+        assert_eq!(root.first_token().unwrap().text(), "let");
+        assert_eq!(root.last_token().unwrap().text(), "\n");
+
+        let semicolon = root.token_at_offset(9).unwrap();
+        assert_eq!(*semicolon.kind(), ";");
+        assert_eq!(semicolon.parent(), statement);
+        assert_eq!(
+            semicolon
+                .prev_sibling_or_token()
+                .and_then(SyntaxElement::into_node),
+            Some(expr)
+        );
+        assert!(semicolon.next_sibling_or_token().is_none());
+    }
+
+    #[test]
+    fn test_sibling_navigation() {
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node("root");
+        for text in ["a", "b", "c"] {
+            builder.start_node("item");
+            builder.token("ident", text);
+            builder.finish_node();
+            builder.token("ws", " ");
+        }
+        builder.finish_node();
+        let root = SyntaxNode::new_root(builder.finish());
+
+        let first = root.first_child().unwrap();
+        let second = first.next_sibling().unwrap();
+        let third = second.next_sibling().unwrap();
+        assert_eq!(second.span(), Span::new(2, 3));
+        assert_eq!(third.span(), Span::new(4, 5));
+        assert!(third.next_sibling().is_none());
+        assert_eq!(third.prev_sibling(), Some(second.clone()));
+        assert_eq!(second.prev_sibling(), Some(first));
+        assert_eq!(root.last_child(), Some(third));
+
+        let space = root.token_at_offset(3).unwrap();
+        assert_eq!(
+            space.prev_sibling_or_token().map(|element| element.span()),
+            Some(Span::new(2, 3))
+        );
+        assert_eq!(
+            space.next_sibling_or_token().map(|element| element.span()),
+            Some(Span::new(4, 5))
+        );
+        assert_eq!(root.descendants().count(), 4);
+    }
+
+    #[test]
+    fn test_replace_shares_unchanged_subtrees() {
+        let root = SyntaxNode::new_root(build_let());
+        let statement = root.first_child().unwrap();
+        let expr = statement.first_child().unwrap();
+
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node("expr");
+        builder.token("int", "42");
+        builder.finish_node();
+        let new_root = SyntaxNode::new_root(expr.replace_with(builder.finish()));
+
+        assert_eq!(new_root.to_string(), "let x = 42;\n");
+        assert_eq!(root.to_string(), "let x = 1;\n");
+
+        let old_children = root.green().children();
+        let new_children = new_root.green().children();
+        assert_eq!(old_children[1], new_children[1]);
+        assert!(
+            !statement
+                .green()
+                .ptr_eq(new_root.first_child().unwrap().green())
+        );
+    }
+
+    #[test]
+    fn test_start_node_at_and_ast_nodes() {
+        struct Binary(SyntaxNode<&'static str>);
+
+        impl AstNode<&'static str> for Binary {
+            fn can_cast(kind: &&'static str) -> bool {
+                *kind == "binary"
+            }
+
+            fn cast(node: SyntaxNode<&'static str>) -> Option<Self> {
+                Self::can_cast(node.kind()).then_some(Binary(node))
+            }
+
+            fn syntax(&self) -> &SyntaxNode<&'static str> {
+                &self.0
+            }
+        }
+
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node("root");
+        let checkpoint = builder.checkpoint();
+        builder.token("int", "1");
+        builder.start_node_at(checkpoint, "binary");
+        builder.token("+", "+");
+        builder.token("int", "2");
+        builder.finish_node();
+        builder.finish_node();
+
+        let root = SyntaxNode::new_root(builder.finish());
+        let binary = root.child_as::<Binary>().unwrap();
+        assert_eq!(binary.syntax().to_string(), "1+2");
+        assert_eq!(binary.syntax().span(), Span::new(0, 3));
+        assert_eq!(root.children_as::<Binary>().count(), 1);
+        assert!(Binary::cast(root).is_none());
+    }
+}
@@ -0,0 +1,366 @@
+//! This module defines the `Diagnostics` struct, which collects errors from every phase of a compiler.
+//!
+//! Instead of printing errors as they are found, the lexer, parser and checker push them into a
+//! `Diagnostics` sink, which sorts, deduplicates and limits them before they are displayed together.
+
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    catalog::{Message, builtin_fallback},
+    error::{Error, Label, Severity, render_basic_diagnostic_colored},
+    render::RenderConfig,
+    span::HasSpan,
+};
+
+/// The fields of an `Error` that identify it for deduplication.
+type ErrorKey = (String, Vec<Label>, String, Severity, Option<String>);
+
+/// Collects errors, warnings and other diagnostics.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     diagnostics::Diagnostics,
+///     error::{Error, Severity},
+///     source::Source,
+///     span::Span,
+/// };
+///
+/// let source = Source::from_str("main.rn", "let x = 10");
+/// let mut diagnostics = Diagnostics::new().with_max_errors(10);
+///
+/// diagnostics.push(Error::new("expected `;`".to_string(), &source, Span::empty(10)));
+/// diagnostics.push(
+///     Error::new("unused variable `x`".to_string(), &source, Span::new(4, 5))
+///         .with_severity(Severity::Warning),
+/// );
+///
+/// assert!(diagnostics.has_errors());
+/// assert_eq!(
+///     diagnostics.summary().unwrap(),
+///     "aborting due to 1 previous error; 1 warning emitted"
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct Diagnostics<'a> {
+    /// The collected diagnostics, in the order they were pushed.
+    errors: Vec<Error<'a>>,
+    /// The keys of the collected diagnostics, used to drop duplicates.
+    seen: HashSet<ErrorKey>,
+    /// The maximum number of errors to collect, if any.
+    max_errors: Option<usize>,
+    /// Whether warnings are promoted to errors.
+    warnings_as_errors: bool,
+    /// The number of errors dropped because the limit was reached.
+    dropped: usize,
+}
+
+impl<'a> Diagnostics<'a> {
+    /// Creates a new, empty `Diagnostics`.
+    pub fn new() -> Self {
+        Diagnostics {
+            errors: Vec::new(),
+            seen: HashSet::new(),
+            max_errors: None,
+            warnings_as_errors: false,
+            dropped: 0,
+        }
+    }
+
+    /// Creates a new `Diagnostics` that collects at most `max_errors` errors.
+    ///
+    /// Errors pushed after the limit is reached are dropped. Other diagnostics are still collected.
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// Creates a new `Diagnostics` that promotes warnings to errors if `enabled` is `true`.
+    pub fn with_warnings_as_errors(mut self, enabled: bool) -> Self {
+        self.warnings_as_errors = enabled;
+        self
+    }
+
+    /// Adds a diagnostic.
+    ///
+    /// Warnings are promoted to errors if requested, identical diagnostics are only kept once,
+    /// and new errors beyond the limit are dropped.
+    pub fn push(&mut self, mut error: Error<'a>) {
+        if self.warnings_as_errors && error.severity() == Severity::Warning {
+            error = error.with_severity(Severity::Error);
+        }
+
+        let key = (
+            error.source_file().filename.to_string(),
+            error.labels().to_vec(),
+            error.message().to_string(),
+            error.severity(),
+            error.code().map(str::to_string),
+        );
+        if !self.seen.insert(key) {
+            return;
+        }
+
+        if error.severity() == Severity::Error && self.limit_reached() {
+            self.dropped += 1;
+            return;
+        }
+
+        self.errors.push(error);
+    }
+
+    /// Adds every diagnostic from the given iterator.
+    pub fn extend(&mut self, errors: impl IntoIterator<Item = Error<'a>>) {
+        for error in errors {
+            self.push(error);
+        }
+    }
+
+    /// Returns `true` if the error limit has been reached.
+    ///
+    /// Phases can check this to stop early instead of producing errors that will be dropped.
+    pub fn limit_reached(&self) -> bool {
+        self.max_errors
+            .is_some_and(|max_errors| self.error_count() >= max_errors)
+    }
+
+    /// Returns the number of collected errors.
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    /// Returns the number of collected warnings.
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    /// Returns the number of collected diagnostics with the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.errors
+            .iter()
+            .filter(|error| error.severity() == severity)
+            .count()
+    }
+
+    /// Returns an iterator over the collected diagnostics, in the order they were pushed.
+    pub fn iter(&self) -> impl Iterator<Item = &Error<'a>> {
+        self.errors.iter()
+    }
+
+    /// Returns the number of errors dropped because the limit was reached.
+    pub fn dropped_count(&self) -> usize {
+        self.dropped
+    }
+
+    /// Returns `true` if any errors were collected.
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Returns the number of collected diagnostics.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns `true` if no diagnostics were collected.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the collected diagnostics, sorted by file and position.
+    ///
+    /// Diagnostics at the same position keep the order they were pushed in.
+    pub fn sorted(&self) -> Vec<&Error<'a>> {
+        let mut errors = self.errors.iter().collect::<Vec<&Error>>();
+        errors.sort_by_key(|error| (error.source_file().filename, error.span()));
+        errors
+    }
+
+    /// Consumes the `Diagnostics`, returning the collected diagnostics sorted by file and position.
+    pub fn into_sorted(mut self) -> Vec<Error<'a>> {
+        self.errors
+            .sort_by_key(|error| (error.source_file().filename, error.span()));
+        self.errors
+    }
+
+    /// Returns a summary of the collected errors and warnings, such as
+    /// `"aborting due to 3 previous errors; 2 warnings emitted"`.
+    ///
+    /// Returns `None` if there are no errors or warnings.
+    pub fn summary(&self) -> Option<String> {
+        self.translated_summary(&RenderConfig::plain())
+    }
+
+    /// Returns the summary, translated through the catalog of the configuration.
+    fn translated_summary(&self, config: &RenderConfig) -> Option<String> {
+        let errors = self.error_count() + self.dropped;
+        let warnings = self.warning_count();
+
+        match (errors, warnings) {
+            (0, 0) => None,
+            (0, warnings) => Some(counted(config, "summary-warnings", warnings)),
+            (errors, 0) => Some(counted(config, "summary-errors", errors)),
+            (errors, warnings) => Some(
+                config.translate(
+                    &Message::new("summary-both", builtin_fallback("summary-both"))
+                        .arg("errors", counted(config, "summary-errors", errors))
+                        .arg("warnings", counted(config, "summary-warnings", warnings)),
+                ),
+            ),
+        }
+    }
+
+    /// Displays the collected diagnostics and the summary on standard error.
+    ///
+    /// Colors are used if standard error is a terminal and `NO_COLOR` is not set.
+    pub fn display(&self) {
+        let config = RenderConfig::default();
+        let mut output = String::new();
+        self.render_with(&mut output, &config, config.color.should_color_stderr())
+            .expect("writing to a String cannot fail");
+        eprint!("{}", output);
+    }
+
+    /// Renders the collected diagnostics, sorted by file and position, followed by the summary.
+    pub fn render(&self, out: &mut impl fmt::Write, config: &RenderConfig) -> fmt::Result {
+        self.render_with(out, config, config.color.should_color(false))
+    }
+
+    /// Renders the collected diagnostics into the given output with the given configuration, with or without colors.
+    fn render_with(
+        &self,
+        out: &mut dyn fmt::Write,
+        config: &RenderConfig,
+        colored: bool,
+    ) -> fmt::Result {
+        for error in self.sorted() {
+            error.render_with(out, config, colored)?;
+            writeln!(out)?;
+        }
+
+        if self.dropped > 0 {
+            let message = counted(config, "summary-dropped", self.dropped);
+            render_basic_diagnostic_colored(out, Severity::Note, &message, config, colored)?;
+        }
+
+        if let Some(summary) = self.translated_summary(config) {
+            let severity = if self.error_count() + self.dropped > 0 {
+                Severity::Error
+            } else {
+                Severity::Warning
+            };
+            render_basic_diagnostic_colored(out, severity, &summary, config, colored)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Translates the built-in message `{base}-one` or `{base}-other`, depending on `count`.
+fn counted(config: &RenderConfig, base: &str, count: usize) -> String {
+    let id = format!("{}-{}", base, if count == 1 { "one" } else { "other" });
+    config.translate(&Message::new(&id, builtin_fallback(&id)).arg("count", count))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{catalog::FluentCatalog, source::Source, span::Span};
+
+    #[test]
+    fn test_diagnostics_sorted_and_deduplicated() {
+        let a = Source::from_str("a.rn", "let x = 10");
+        let b = Source::from_str("b.rn", "let y = 20");
+
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(Error::new("second".to_string(), &b, Span::new(0, 3)));
+        diagnostics.push(Error::new("third".to_string(), &a, Span::new(4, 5)));
+        diagnostics.push(Error::new("first".to_string(), &a, Span::new(0, 3)));
+        diagnostics.push(Error::new("first".to_string(), &a, Span::new(0, 3)));
+
+        let messages = diagnostics
+            .sorted()
+            .iter()
+            .map(|error| error.message())
+            .collect::<Vec<&str>>();
+        assert_eq!(messages, vec!["first", "third", "second"]);
+        assert_eq!(diagnostics.len(), 3);
+    }
+
+    #[test]
+    fn test_diagnostics_limits() {
+        let source = Source::from_str("a.rn", "let x = 10");
+
+        let mut diagnostics = Diagnostics::new()
+            .with_max_errors(2)
+            .with_warnings_as_errors(true);
+        diagnostics.push(
+            Error::new("unused".to_string(), &source, Span::new(4, 5))
+                .with_severity(Severity::Warning),
+        );
+        diagnostics.push(Error::new("one".to_string(), &source, Span::new(0, 3)));
+        diagnostics.push(Error::new("two".to_string(), &source, Span::new(8, 10)));
+        diagnostics.push(Error::new("one".to_string(), &source, Span::new(0, 3)));
+        diagnostics.push(Error::new("two".to_string(), &source, Span::new(8, 10)));
+
+        assert!(diagnostics.limit_reached());
+        assert_eq!(diagnostics.error_count(), 2);
+        assert_eq!(diagnostics.warning_count(), 0);
+        assert_eq!(diagnostics.dropped_count(), 1);
+        assert_eq!(
+            diagnostics.summary().unwrap(),
+            "aborting due to 3 previous errors"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_render() {
+        let source = Source::from_str("a.rn", "let x = 10");
+
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(
+            Error::new("unused".to_string(), &source, Span::new(4, 5))
+                .with_severity(Severity::Warning),
+        );
+
+        let mut output = String::new();
+        diagnostics
+            .render(&mut output, &RenderConfig::plain())
+            .unwrap();
+        assert!(output.starts_with("warning: unused\n"));
+        assert!(output.ends_with("\nwarning: 1 warning emitted\n"));
+    }
+
+    #[test]
+    fn test_diagnostics_render_translated() {
+        let source = Source::from_str("a.rn", "let x = 10");
+
+        let mut diagnostics = Diagnostics::new().with_max_errors(1);
+        diagnostics.push(Error::new("one".to_string(), &source, Span::new(0, 3)));
+        diagnostics.push(Error::new("two".to_string(), &source, Span::new(4, 5)));
+
+        let catalog = FluentCatalog::from_entries([
+            ("summary-errors-other", "abandon après { $count } erreurs"),
+            (
+                "summary-dropped-one",
+                "{ $count } erreur de plus non affichée",
+            ),
+        ]);
+        let config = RenderConfig {
+            catalog: Some(Arc::new(catalog)),
+            ..RenderConfig::plain()
+        };
+
+        let mut output = String::new();
+        diagnostics.render(&mut output, &config).unwrap();
+        assert!(output.contains("note: 1 erreur de plus non affichée\n"));
+        assert!(output.ends_with("error: abandon après 2 erreurs\n"));
+        assert_eq!(
+            diagnostics.summary().unwrap(),
+            "aborting due to 2 previous errors"
+        );
+    }
+}
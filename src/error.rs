@@ -1,187 +1,712 @@
 //! This module provides error handling utilities.
 
-use colored::*;
+use std::{error::Error as StdError, fmt, io};
 
 use crate::{
+    catalog::{Message, builtin_fallback},
+    expansion::{Expansion, ExpansionId, ExpansionKind, ExpansionTable},
+    render::{
+        ColorChoice, RenderConfig, Renderer, line_number_width, suggestion_line_number_width,
+    },
     source::Source,
-    span::{Span, location_to_line_col},
+    span::{HasSpan, Span},
 };
 
+/// Describes how serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// A problem that prevents compilation.
+    Error,
+    /// A problem that does not prevent compilation, such as a lint.
+    Warning,
+    /// Informational output.
+    Info,
+    /// Additional information about another diagnostic.
+    Note,
+    /// Advice on how to fix a problem.
+    Help,
+}
+
+impl Severity {
+    /// Returns the name of the severity as it appears in diagnostic headers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        }
+    }
+
+    /// Returns the ID of the built-in message naming the severity, such as `severity-error`.
+    pub(crate) fn message_id(&self) -> &'static str {
+        match self {
+            Severity::Error => "severity-error",
+            Severity::Warning => "severity-warning",
+            Severity::Info => "severity-info",
+            Severity::Note => "severity-note",
+            Severity::Help => "severity-help",
+        }
+    }
+}
+
+/// Describes how confident a suggestion is, and so whether it can be applied automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended and can be applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `<type>` that the user has to fill in.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
+}
+
+impl Applicability {
+    /// Returns the name of the applicability, as used in machine-readable output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "MachineApplicable",
+            Applicability::MaybeIncorrect => "MaybeIncorrect",
+            Applicability::HasPlaceholders => "HasPlaceholders",
+            Applicability::Unspecified => "Unspecified",
+        }
+    }
+}
+
+/// Represents a replacement of a span in the source code with new text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    /// The span in the source code to replace.
+    pub span: Span,
+    /// The text to replace the span with.
+    pub replacement: String,
+    /// How confident the suggestion is.
+    pub applicability: Applicability,
+}
+
+/// Represents a `help:` entry of an error, optionally suggesting a replacement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Help {
+    /// The help message.
+    pub message: String,
+    /// The suggested replacement, if any.
+    pub suggestion: Option<Suggestion>,
+}
+
+/// Describes how a label is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LabelStyle {
+    /// The label marks the main cause of the error and is underlined with `^`.
+    Primary,
+    /// The label adds context to the error and is underlined with `-`.
+    Secondary,
+}
+
+/// Represents a span in the source code with an optional message attached to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Label {
+    /// The span in the source code the label points at.
+    pub span: Span,
+    /// The message printed next to the underline, if any.
+    pub message: Option<String>,
+    /// How the label is rendered.
+    pub style: LabelStyle,
+}
+
+impl Label {
+    /// Creates a new primary `Label` without a message.
+    pub fn primary(span: Span) -> Self {
+        Label {
+            span,
+            message: None,
+            style: LabelStyle::Primary,
+        }
+    }
+
+    /// Creates a new secondary `Label` with the given message.
+    pub fn secondary(span: Span, message: String) -> Self {
+        Label {
+            span,
+            message: Some(message),
+            style: LabelStyle::Secondary,
+        }
+    }
+}
+
 /// Represents an advanced error.
 #[derive(Debug)]
 pub struct Error<'a> {
+    /// The contents of the error, boxed to keep `Result`s small.
+    inner: Box<ErrorInner<'a>>,
+}
+
+/// The contents of an `Error`.
+#[derive(Debug)]
+struct ErrorInner<'a> {
     /// The error message describing the issue.
     message: String,
+    /// The localizable definition of the message, if the error was created from one.
+    localized: Option<Message>,
+    /// How serious the error is.
+    severity: Severity,
+    /// The error code, such as `E0042`, if any.
+    code: Option<String>,
     /// The source code where the error occurred.
     source: &'a Source<'a>,
-    /// The span in the source code where the error occurred.
-    span: Span,
+    /// The labels pointing into the source code. The first label is the primary one.
+    labels: Vec<Label>,
     /// The context of the error, if any.
     context: Vec<String>,
     /// Notes or additional information about the error.
     notes: Vec<String>,
+    /// Help messages, optionally carrying suggested replacements.
+    helps: Vec<Help>,
+    /// The expansion the error occurred in, if the source is expanded text.
+    expansion: Option<(&'a ExpansionTable<'a>, ExpansionId)>,
+    /// The underlying error that caused this one, if any.
+    cause: Option<Box<dyn StdError + Send + Sync>>,
 }
 
 impl<'a> Error<'a> {
     /// Creates a new `Error`
     pub fn new(message: String, source: &'a Source<'a>, span: Span) -> Self {
         Error {
-            message,
-            source,
-            span,
-            context: Vec::new(),
-            notes: Vec::new(),
+            inner: Box::new(ErrorInner {
+                message,
+                localized: None,
+                severity: Severity::Error,
+                code: None,
+                source,
+                labels: vec![Label::primary(span)],
+                context: Vec::new(),
+                notes: Vec::new(),
+                helps: Vec::new(),
+                expansion: None,
+                cause: None,
+            }),
         }
     }
 
+    /// Creates a new `Error` from a localizable message.
+    ///
+    /// `message()` returns the English text, while rendering translates the message
+    /// through the catalog of the `RenderConfig`, if any.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    ///
+    /// use runic_kit::{
+    ///     catalog::{FluentCatalog, Message},
+    ///     error::Error,
+    ///     render::RenderConfig,
+    ///     source::Source,
+    ///     span::Span,
+    /// };
+    ///
+    /// let source = Source::from_str("main.rn", "let x = 10");
+    /// let message = Message::new("expected-token", "expected `{ $token }`").arg("token", ";");
+    /// let error = Error::localized(message, &source, Span::empty(10));
+    /// assert_eq!(error.message(), "expected `;`");
+    ///
+    /// let catalog = FluentCatalog::from_entries([
+    ///     ("severity-error", "erreur"),
+    ///     ("expected-token", "`{ $token }` attendu"),
+    /// ]);
+    /// let config = RenderConfig {
+    ///     catalog: Some(Arc::new(catalog)),
+    ///     ..RenderConfig::plain()
+    /// };
+    ///
+    /// let mut output = String::new();
+    /// error.render(&mut output, &config).unwrap();
+    /// assert!(output.starts_with("erreur: `;` attendu\n"));
+    /// ```
+    pub fn localized(message: Message, source: &'a Source<'a>, span: Span) -> Self {
+        let mut error = Error::new(message.resolve(None), source, span);
+        error.inner.localized = Some(message);
+        error
+    }
+
+    /// Creates a new `Error`, changing its severity.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{
+    ///     error::{Error, Severity},
+    ///     source::Source,
+    ///     span::Span,
+    /// };
+    ///
+    /// let source = Source::from_str("main.rn", "let x = 10;");
+    /// let warning = Error::new("unused variable `x`".to_string(), &source, Span::new(4, 5))
+    ///     .with_severity(Severity::Warning)
+    ///     .with_code("W0001".to_string());
+    /// warning.display(); // Displays: "warning[W0001]: unused variable `x`" ...
+    /// ```
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.inner.severity = severity;
+        self
+    }
+
+    /// Creates a new `Error`, setting its error code.
+    pub fn with_code(mut self, code: String) -> Self {
+        self.inner.code = Some(code);
+        self
+    }
+
+    /// Creates a new `Error`, attaching the given message to the primary span.
+    pub fn with_primary_label(mut self, message: String) -> Self {
+        self.inner.labels[0].message = Some(message);
+        self
+    }
+
+    /// Creates a new `Error`, adding a secondary label with the given message.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{error::Error, source::Source, span::Span};
+    ///
+    /// let source = Source::from_str("main.rn", "let x = 1;\nlet x = 2;");
+    /// let error = Error::new("duplicate definition".to_string(), &source, Span::new(15, 16))
+    ///     .with_primary_label("redefined here".to_string())
+    ///     .with_label(Span::new(4, 5), "first defined here".to_string());
+    /// error.display();
+    /// ```
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.inner.labels.push(Label::secondary(span, message));
+        self
+    }
+
     /// Creates a new `Error`, adding the given context to the error.
     pub fn with_context(mut self, context: String) -> Self {
-        self.context.push(context);
+        self.inner.context.push(context);
         self
     }
 
     /// Creates a new `Error`, adding the given note to the error.
     pub fn with_note(mut self, note: String) -> Self {
-        self.notes.push(note);
+        self.inner.notes.push(note);
+        self
+    }
+
+    /// Creates a new `Error`, adding the given help message to the error.
+    pub fn with_help(mut self, help: String) -> Self {
+        self.inner.helps.push(Help {
+            message: help,
+            suggestion: None,
+        });
+        self
+    }
+
+    /// Creates a new `Error`, adding a help message that suggests replacing `span` with `replacement`.
+    ///
+    /// When rendered, the suggestion is shown as the patched source lines.
+    /// Machine-applicable suggestions can be applied with `fix::apply_suggestions`.
+    pub fn with_suggestion(
+        mut self,
+        help: String,
+        span: Span,
+        replacement: String,
+        applicability: Applicability,
+    ) -> Self {
+        self.inner.helps.push(Help {
+            message: help,
+            suggestion: Some(Suggestion {
+                span,
+                replacement,
+                applicability,
+            }),
+        });
         self
     }
 
-    /// Displays the error in a human-readable format.
+    /// Creates a new `Error`, marking it as occurring in text produced by the given expansion.
+    ///
+    /// When displayed, the error walks the chain of call sites recorded in `table`.
+    pub fn with_expansion(mut self, table: &'a ExpansionTable<'a>, id: ExpansionId) -> Self {
+        self.inner.expansion = Some((table, id));
+        self
+    }
+
+    /// Creates a new `Error`, recording the underlying error that caused it.
+    ///
+    /// The cause is returned by `std::error::Error::source` and shown as `caused by:` when rendered.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use std::error::Error as _;
+    ///
+    /// use runic_kit::{error::Error, source::Source, span::Span};
+    ///
+    /// let source = Source::from_str("main.rn", "import \"util.rn\";");
+    /// let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
+    /// let error = Error::new("cannot import `util.rn`".to_string(), &source, Span::new(7, 16))
+    ///     .with_cause(io_error);
+    ///
+    /// assert_eq!(error.source().unwrap().to_string(), "file not found");
+    /// ```
+    pub fn with_cause(mut self, cause: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        self.inner.cause = Some(cause.into());
+        self
+    }
+
+    /// Converts the error into an owned `Report`, rendering it without colors.
+    pub fn into_report(self) -> Report {
+        Report::from(self)
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.inner.message
+    }
+
+    /// Returns the source code where the error occurred.
+    pub fn source_file(&self) -> &'a Source<'a> {
+        self.inner.source
+    }
+
+    /// Returns the severity of the error.
+    pub fn severity(&self) -> Severity {
+        self.inner.severity
+    }
+
+    /// Returns the error code, if any.
+    pub fn code(&self) -> Option<&str> {
+        self.inner.code.as_deref()
+    }
+
+    /// Returns the context of the error.
+    pub fn context(&self) -> &[String] {
+        &self.inner.context
+    }
+
+    /// Returns the notes of the error.
+    pub fn notes(&self) -> &[String] {
+        &self.inner.notes
+    }
+
+    /// Returns the help entries of the error.
+    pub fn helps(&self) -> &[Help] {
+        &self.inner.helps
+    }
+
+    /// Returns the labels of the error. The first label is the primary one.
+    pub fn labels(&self) -> &[Label] {
+        &self.inner.labels
+    }
+
+    /// Displays the error in a human-readable format on standard error.
+    ///
+    /// Colors are used if standard error is a terminal and `NO_COLOR` is not set.
     pub fn display(&self) {
-        let (start_line, start_col) = location_to_line_col(&self.source.code, self.span.start);
-        let (end_line, mut end_col) = location_to_line_col(&self.source.code, self.span.end);
-        end_col -= 1;
+        let config = RenderConfig::default();
+        let mut output = String::new();
+        self.render_with(&mut output, &config, config.color.should_color_stderr())
+            .expect("writing to a String cannot fail");
+        eprint!("{}", output);
+    }
+
+    /// Renders the error in a human-readable format into the given output.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{error::Error, render::RenderConfig, source::Source, span::Span};
+    ///
+    /// let source = Source::from_str("main.rn", "let x = 10");
+    /// let error = Error::new("expected `;`".to_string(), &source, Span::empty(10));
+    ///
+    /// let mut output = String::new();
+    /// error.render(&mut output, &RenderConfig::plain()).unwrap();
+    /// assert!(output.starts_with("error: expected `;`"));
+    /// ```
+    pub fn render(&self, out: &mut impl fmt::Write, config: &RenderConfig) -> fmt::Result {
+        self.render_with(out, config, config.color.should_color(false))
+    }
+
+    /// Renders the error in a human-readable format into the given writer.
+    pub fn write_to(&self, writer: &mut impl io::Write, config: &RenderConfig) -> io::Result<()> {
+        let mut output = String::new();
+        self.render(&mut output, config)
+            .expect("writing to a String cannot fail");
+        writer.write_all(output.as_bytes())
+    }
+
+    /// Renders the error into the given output with the given configuration, with or without colors.
+    pub(crate) fn render_with(
+        &self,
+        out: &mut dyn fmt::Write,
+        config: &RenderConfig,
+        colored: bool,
+    ) -> fmt::Result {
+        let inner = &self.inner;
+        let frames = match inner.expansion {
+            Some((table, id)) => table.backtrace(id).collect::<Vec<&Expansion>>(),
+            None => Vec::new(),
+        };
+
+        let suggestions = inner
+            .helps
+            .iter()
+            .filter_map(|help| Some((&help.message, help.suggestion.as_ref()?)))
+            .collect::<Vec<(&String, &Suggestion)>>();
+
+        let number_of_spaces = frames
+            .iter()
+            .map(|frame| line_number_width(frame.call_source, &[frame.call_site]))
+            .chain(
+                suggestions
+                    .iter()
+                    .map(|(_, suggestion)| suggestion_line_number_width(inner.source, suggestion)),
+            )
+            .fold(
+                line_number_width(
+                    inner.source,
+                    &inner.labels.iter().map(|l| l.span).collect::<Vec<Span>>(),
+                ),
+                usize::max,
+            );
 
-        let number_of_spaces = start_line.max(end_line).to_string().len();
+        let mut renderer = Renderer::new(out, config, colored, number_of_spaces);
 
-        eprintln!(
+        let severity = config.builtin(inner.severity.message_id());
+        let header = match &inner.code {
+            Some(code) => format!("{}[{}]", severity, code),
+            None => severity,
+        };
+        let message = match &inner.localized {
+            Some(message) => config.translate(message),
+            None => inner.message.clone(),
+        };
+        let header = format!(
             "{}{} {}",
-            "error".red().bold(),
-            ":".bold(),
-            self.message.bold()
+            renderer.paint(&header, config.theme.severity(inner.severity)),
+            renderer.paint(":", config.theme.styles.emphasis),
+            renderer.paint(&message, config.theme.styles.emphasis)
         );
+        renderer.line(&header)?;
 
-        if start_line == end_line {
-            if start_col == end_col {
-                eprintln!(
-                    "{}{} {}:{}:{}",
-                    " ".repeat(number_of_spaces),
-                    "-->".cyan().bold(),
-                    self.source.filename,
-                    start_line,
-                    start_col
-                );
-            } else {
-                eprintln!(
-                    "{}{} {}:{}:{}-{}",
-                    " ".repeat(number_of_spaces),
-                    "-->".cyan().bold(),
-                    self.source.filename,
-                    start_line,
-                    start_col,
-                    end_col
-                );
-            }
-        } else {
-            eprintln!(
-                "{}{} {}:{}:{}-{}:{}",
-                " ".repeat(number_of_spaces),
-                "-->".cyan().bold(),
-                self.source.filename,
-                start_line,
-                start_col,
-                end_line,
-                end_col
-            );
+        renderer.location(
+            inner.source,
+            self.span(),
+            &config.glyph("location-arrow", config.theme.glyphs.arrow),
+        )?;
+        renderer.empty_gutter()?;
+        renderer.snippet(
+            inner.source,
+            &inner.labels,
+            config.theme.severity(inner.severity),
+        )?;
+
+        for frame in frames.iter() {
+            let label = match frame.kind {
+                ExpansionKind::Macro(_) => config.builtin("expansion-macro"),
+                ExpansionKind::Include(_) => config.builtin("expansion-include"),
+            };
+
+            renderer.empty_gutter()?;
+            renderer.location(
+                frame.call_source,
+                frame.call_site,
+                &config.glyph("expansion-arrow", config.theme.glyphs.expansion_arrow),
+            )?;
+            renderer.empty_gutter()?;
+            renderer.snippet(
+                frame.call_source,
+                &[Label::secondary(frame.call_site, label)],
+                config.theme.severity(inner.severity),
+            )?;
         }
 
-        let lines = self.source.code.lines().collect::<Vec<&str>>();
-        let lines = lines
-            .iter()
-            .skip(start_line - 1)
-            .take(end_line - start_line + 1);
-
-        eprintln!("{} {}", " ".repeat(number_of_spaces), "|".cyan().bold());
-
-        for (line_index, line) in lines.enumerate() {
-            let line_number = start_line + line_index;
-
-            if line_number == start_line && line_number == end_line {
-                eprintln!(
-                    "{}{} {} {}",
-                    line_number.to_string().cyan().bold(),
-                    " ".repeat(number_of_spaces - line_number.to_string().len()),
-                    "|".cyan().bold(),
-                    line
-                );
-                eprintln!(
-                    "{} {} {}{}",
-                    " ".repeat(number_of_spaces),
-                    "|".cyan().bold(),
-                    " ".repeat(start_col - 1),
-                    "^".repeat(end_col - start_col + 1).red().bold()
-                );
-                continue;
-            }
-
-            eprintln!(
-                "{}{} {} {}",
-                line_number.to_string().cyan().bold(),
-                " ".repeat(number_of_spaces - line_number.to_string().len()),
-                "|".cyan().bold(),
-                line
-            );
+        let origin = frames.iter().find_map(|frame| match &frame.kind {
+            ExpansionKind::Macro(name) => Some(name),
+            ExpansionKind::Include(_) => None,
+        });
 
-            if line_number == start_line {
-                eprintln!(
-                    "{} {} {}{}",
-                    " ".repeat(number_of_spaces),
-                    "|".cyan().bold(),
-                    " ".repeat(start_col - 1),
-                    "^".repeat(line.len() - start_col + 1).red().bold()
-                );
-            } else if line_number == end_line {
-                eprintln!(
-                    "{} {} {}",
-                    " ".repeat(number_of_spaces),
-                    "|".cyan().bold(),
-                    "^".repeat(end_col + 1).red().bold()
-                );
-            } else {
-                eprintln!(
-                    "{} {} {}",
-                    " ".repeat(number_of_spaces),
-                    "|".cyan().bold(),
-                    "^".repeat(line.len()).red().bold()
-                );
-            }
-        }
-
-        if !self.context.is_empty() || !self.notes.is_empty() {
-            eprintln!("{} {}", " ".repeat(number_of_spaces), "|".cyan().bold());
-        }
-
-        for context in self.context.iter() {
-            eprintln!(
-                "{} {} {}",
-                " ".repeat(number_of_spaces),
-                "=".cyan().bold(),
-                context
-            );
+        if !inner.context.is_empty()
+            || !inner.notes.is_empty()
+            || inner.helps.len() > suggestions.len()
+            || origin.is_some()
+            || inner.cause.is_some()
+        {
+            renderer.empty_gutter()?;
+        }
+
+        for context in inner.context.iter() {
+            renderer.footer(None, context)?;
+        }
+
+        let note = format!("{}:", config.builtin("label-note"));
+        for text in inner.notes.iter() {
+            renderer.footer(Some(&note), text)?;
+        }
+
+        let help = format!("{}:", config.builtin("label-help"));
+        for text in inner.helps.iter().filter(|help| help.suggestion.is_none()) {
+            renderer.footer(Some(&help), &text.message)?;
+        }
+
+        if let Some(name) = origin {
+            let origin = Message::new("expansion-origin", builtin_fallback("expansion-origin"))
+                .arg("name", name);
+            renderer.footer(Some(&note), &config.translate(&origin))?;
         }
 
-        for note in self.notes.iter() {
-            eprintln!(
-                "{} {} {} {}",
-                " ".repeat(number_of_spaces),
-                "=".cyan().bold(),
-                "note:".bold(),
-                note
+        let caused_by = format!("{}:", config.builtin("label-caused-by"));
+        let mut cause = inner.cause.as_deref().map(|cause| cause as &dyn StdError);
+        while let Some(error) = cause {
+            renderer.footer(Some(&caused_by), &error.to_string())?;
+            cause = error.source();
+        }
+
+        for (message, suggestion) in suggestions {
+            let header = format!(
+                "{}{} {}",
+                renderer.paint(
+                    &config.builtin("label-help"),
+                    config.theme.severity(Severity::Help)
+                ),
+                renderer.paint(":", config.theme.styles.emphasis),
+                message
             );
+            renderer.line(&header)?;
+            renderer.empty_gutter()?;
+            renderer.suggestion(inner.source, suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Error<'_> {
+    /// Renders the error without colors.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render_with(f, &RenderConfig::plain(), false)
+    }
+}
+
+impl StdError for Error<'_> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.inner
+            .cause
+            .as_deref()
+            .map(|cause| cause as &(dyn StdError + 'static))
+    }
+}
+
+impl HasSpan for Error<'_> {
+    fn span(&self) -> Span {
+        self.inner.labels[0].span
+    }
+}
+
+/// Represents an owned, rendered error.
+///
+/// An `Error` borrows its `Source`, so it cannot outlive it. A `Report` keeps the rendered text
+/// instead, so it can be returned from `main`, boxed as `Box<dyn std::error::Error>` or passed to
+/// other error handling libraries that require `'static` errors.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{error::Report, source::Source};
+///
+/// fn load() -> Result<Source<'static>, Report> {
+///     Ok(Source::new("missing_file.rn")?)
+/// }
+///
+/// assert!(load().unwrap_err().to_string().starts_with("error: "));
+/// ```
+#[derive(Debug)]
+pub struct Report {
+    /// The message of the error.
+    message: String,
+    /// The severity of the error.
+    severity: Severity,
+    /// The error code, if any.
+    code: Option<String>,
+    /// The error rendered without colors.
+    rendered: String,
+    /// The underlying error that caused this one, if any.
+    cause: Option<Box<dyn StdError + Send + Sync>>,
+}
+
+impl Report {
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the severity of the error.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the error code, if any.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+}
+
+impl From<Error<'_>> for Report {
+    fn from(error: Error<'_>) -> Self {
+        let rendered = error.to_string();
+        let inner = *error.inner;
+        Report {
+            message: inner.message,
+            severity: inner.severity,
+            code: inner.code,
+            rendered,
+            cause: inner.cause,
         }
     }
 }
 
+impl From<io::Error> for Report {
+    fn from(error: io::Error) -> Self {
+        let message = error.to_string();
+        let mut rendered = String::new();
+        render_basic_diagnostic_colored(
+            &mut rendered,
+            Severity::Error,
+            &message,
+            &RenderConfig::plain(),
+            false,
+        )
+        .expect("writing to a String cannot fail");
+        Report {
+            message,
+            severity: Severity::Error,
+            code: None,
+            rendered,
+            cause: Some(Box::new(error)),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    /// Writes the rendered error.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rendered)
+    }
+}
+
+impl StdError for Report {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn StdError + 'static))
+    }
+}
+
 /// Displays a basic error message.
 ///
 /// # Usage
@@ -192,15 +717,91 @@ impl<'a> Error<'a> {
 /// basic_error("An error occurred".to_string()); // Displays: "error: An error occurred"
 /// ```
 pub fn basic_error(message: String) {
-    eprintln!("{}: {}", "error".red().bold(), message.bold());
+    basic_diagnostic(Severity::Error, message);
+}
+
+/// Displays a basic diagnostic message with the given severity.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::error::{Severity, basic_diagnostic};
+///
+/// basic_diagnostic(Severity::Warning, "A warning occurred".to_string()); // Displays: "warning: A warning occurred"
+/// ```
+pub fn basic_diagnostic(severity: Severity, message: String) {
+    let colored = ColorChoice::Auto.should_color_stderr();
+    let mut output = String::new();
+    render_basic_diagnostic_colored(
+        &mut output,
+        severity,
+        &message,
+        &RenderConfig::default(),
+        colored,
+    )
+    .expect("writing to a String cannot fail");
+    eprint!("{}", output);
+}
+
+/// Renders a basic diagnostic message with the given severity into the given output.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     error::{Severity, render_basic_diagnostic},
+///     render::RenderConfig,
+/// };
+///
+/// let mut output = String::new();
+/// render_basic_diagnostic(&mut output, Severity::Error, "An error occurred", &RenderConfig::plain()).unwrap();
+/// assert_eq!(output, "error: An error occurred\n");
+/// ```
+pub fn render_basic_diagnostic(
+    out: &mut impl fmt::Write,
+    severity: Severity,
+    message: &str,
+    config: &RenderConfig,
+) -> fmt::Result {
+    render_basic_diagnostic_colored(
+        out,
+        severity,
+        message,
+        config,
+        config.color.should_color(false),
+    )
+}
+
+/// Renders a basic diagnostic message into the given output with the given configuration, with or without colors.
+pub(crate) fn render_basic_diagnostic_colored(
+    out: &mut dyn fmt::Write,
+    severity: Severity,
+    message: &str,
+    config: &RenderConfig,
+    colored: bool,
+) -> fmt::Result {
+    writeln!(
+        out,
+        "{}: {}",
+        config
+            .theme
+            .severity(severity)
+            .paint(&config.builtin(severity.message_id()), colored),
+        config.theme.styles.emphasis.paint(message, colored)
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    // TODO: check stdout for expected output
+    use std::sync::Arc;
 
     use super::*;
-    use crate::span::Span;
+    use crate::{catalog::FluentCatalog, span::Span};
+
+    /// Joins the given lines, terminating each with a newline.
+    fn lines(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
 
     #[test]
     fn test_error_display_single_line() {
@@ -212,6 +813,19 @@ mod tests {
         let error = Error::new("Syntax error".to_string(), &source, span)
             .with_context("In function main".to_string())
             .with_note("Check the syntax".to_string());
+        assert_eq!(
+            error.to_string(),
+            lines(&[
+                "error: Syntax error",
+                " --> test.rs:2:1-13",
+                "  |",
+                "2 |     println!(\"Hello, world!\");",
+                "  | ^^^^^^^^^^^^^",
+                "  |",
+                "  = In function main",
+                "  = note: Check the syntax",
+            ])
+        );
         error.display();
     }
 
@@ -225,12 +839,271 @@ mod tests {
         let error = Error::new("Syntax error".to_string(), &source, span)
             .with_context("In function main".to_string())
             .with_note("Check the syntax".to_string());
+        assert_eq!(
+            error.to_string(),
+            lines(&[
+                "error: Syntax error",
+                " --> test.rs:2:1-28",
+                "  |",
+                "2 |     println!(\"Hello, world!\");",
+                "  | ^^^^^^^^^^^^^^^^^^^^^^^^^^^^",
+                "  |",
+                "  = In function main",
+                "  = note: Check the syntax",
+            ])
+        );
+        error.display();
+    }
+
+    #[test]
+    fn test_error_display_labels() {
+        let source = Source::from_str(
+            "test.rs",
+            "let a = &mut x;\nlet b = &mut x;\n\n\n\nuse(a, b);",
+        );
+        let error = Error::new(
+            "Cannot borrow `x` as mutable more than once".to_string(),
+            &source,
+            Span::new(24, 30),
+        )
+        .with_primary_label("second mutable borrow occurs here".to_string())
+        .with_label(
+            Span::new(8, 14),
+            "first mutable borrow occurs here".to_string(),
+        )
+        .with_label(Span::new(20, 21), "second borrow assigned here".to_string())
+        .with_label(
+            Span::new(39, 40),
+            "first borrow later used here".to_string(),
+        );
+        assert_eq!(
+            error.to_string(),
+            lines(&[
+                "error: Cannot borrow `x` as mutable more than once",
+                " --> test.rs:2:9-14",
+                "  |",
+                "1 | let a = &mut x;",
+                "  |         ------ first mutable borrow occurs here",
+                "2 | let b = &mut x;",
+                "  |     -   ^^^^^^ second mutable borrow occurs here",
+                "  |     |",
+                "  |     second borrow assigned here",
+                "...",
+                "6 | use(a, b);",
+                "  |     - first borrow later used here",
+            ])
+        );
+        error.display();
+    }
+
+    #[test]
+    fn test_error_display_expansion() {
+        let main = Source::from_str("main.c", "int x = SQUARE(y);");
+        let expanded = Source::from_str("<macro SQUARE>", "y * y");
+
+        let mut table = ExpansionTable::new();
+        let id = table.push(
+            ExpansionKind::Macro("SQUARE".to_string()),
+            &expanded,
+            &main,
+            Span::new(8, 17),
+            None,
+        );
+
+        let error = Error::new("Undefined variable".to_string(), &expanded, Span::new(0, 1))
+            .with_expansion(&table, id);
+        assert_eq!(
+            error.to_string(),
+            lines(&[
+                "error: Undefined variable",
+                " --> <macro SQUARE>:1:1",
+                "  |",
+                "1 | y * y",
+                "  | ^",
+                "  |",
+                " ::: main.c:1:9-17",
+                "  |",
+                "1 | int x = SQUARE(y);",
+                "  |         --------- in this macro invocation",
+                "  |",
+                "  = note: this error originates in the macro `SQUARE`",
+            ])
+        );
+        error.display();
+    }
+
+    #[test]
+    fn test_error_display_severity() {
+        let source = Source::from_str("test.rs", "let x = 10");
+        let error = Error::new("Unused variable `x`".to_string(), &source, Span::new(4, 5))
+            .with_severity(Severity::Warning)
+            .with_code("W0001".to_string())
+            .with_help("Remove the variable".to_string())
+            .with_suggestion(
+                "Prefix it with an underscore".to_string(),
+                Span::new(4, 5),
+                "_x".to_string(),
+                Applicability::MaybeIncorrect,
+            );
+
+        assert_eq!(error.severity(), Severity::Warning);
+        assert_eq!(error.code(), Some("W0001"));
+        assert_eq!(error.helps().len(), 2);
+        assert_eq!(
+            error.to_string(),
+            lines(&[
+                "warning[W0001]: Unused variable `x`",
+                " --> test.rs:1:5",
+                "  |",
+                "1 | let x = 10",
+                "  |     ^",
+                "  |",
+                "  = help: Remove the variable",
+                "help: Prefix it with an underscore",
+                "  |",
+                "1 | let _x = 10",
+                "  |     ~~",
+            ])
+        );
         error.display();
     }
 
+    #[test]
+    fn test_error_display_suggestions() {
+        let source = Source::from_str("test.rs", "let x = 10\nlet y = x +  1;");
+        let error = Error::new("Syntax error".to_string(), &source, Span::empty(10))
+            .with_suggestion(
+                "Add a semicolon".to_string(),
+                Span::empty(10),
+                ";".to_string(),
+                Applicability::MachineApplicable,
+            )
+            .with_suggestion(
+                "Remove the extra space".to_string(),
+                Span::new(22, 23),
+                String::new(),
+                Applicability::MachineApplicable,
+            );
+
+        assert_eq!(
+            error.to_string(),
+            lines(&[
+                "error: Syntax error",
+                " --> test.rs:1:11",
+                "  |",
+                "1 | let x = 10",
+                "  |           ^",
+                "help: Add a semicolon",
+                "  |",
+                "1 | let x = 10;",
+                "  |           +",
+                "help: Remove the extra space",
+                "  |",
+                "2 | let y = x +  1;",
+                "  |            -",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_error_cause() {
+        let source = Source::from_str("test.rs", "import \"util.rs\";");
+        let io_error = io::Error::new(io::ErrorKind::NotFound, "file not found");
+        let error = Error::new(
+            "Cannot import `util.rs`".to_string(),
+            &source,
+            Span::new(7, 16),
+        )
+        .with_cause(io_error);
+
+        assert_eq!(error.source().unwrap().to_string(), "file not found");
+        assert!(
+            error
+                .to_string()
+                .ends_with("  = caused by: file not found\n")
+        );
+
+        let boxed: Box<dyn StdError + '_> = Box::new(error);
+        assert!(boxed.source().is_some());
+    }
+
+    #[test]
+    fn test_report() {
+        let source = Source::from_str("test.rs", "let x = 10");
+        let error = Error::new("Syntax error".to_string(), &source, Span::new(4, 5))
+            .with_code("E0001".to_string())
+            .with_cause(io::Error::other("inner"));
+        let rendered = error.to_string();
+
+        let report: Box<dyn StdError + Send + Sync + 'static> = Box::new(error.into_report());
+        assert_eq!(report.to_string(), rendered);
+        assert_eq!(report.source().unwrap().to_string(), "inner");
+
+        let report = Report::from(io::Error::new(io::ErrorKind::NotFound, "not found"));
+        assert_eq!(report.message(), "not found");
+        assert_eq!(report.to_string(), "error: not found\n");
+    }
+
+    #[test]
+    fn test_error_display_localized() {
+        let source = Source::from_str("test.rs", "let x = 10");
+        let error = Error::localized(
+            Message::new("unused-variable", "unused variable `{ $name }`").arg("name", "x"),
+            &source,
+            Span::new(4, 5),
+        )
+        .with_severity(Severity::Warning)
+        .with_note("Prefix it with an underscore".to_string());
+
+        let catalog = FluentCatalog::from_entries([
+            ("severity-warning", "avertissement"),
+            ("label-note", "remarque"),
+            ("location-arrow", "==>"),
+            ("unused-variable", "variable inutilisée `{ $name }`"),
+        ]);
+        let config = RenderConfig {
+            catalog: Some(Arc::new(catalog)),
+            ..RenderConfig::plain()
+        };
+
+        let mut output = String::new();
+        error.render(&mut output, &config).unwrap();
+        assert_eq!(
+            output,
+            lines(&[
+                "avertissement: variable inutilisée `x`",
+                " ==> test.rs:1:5",
+                "  |",
+                "1 | let x = 10",
+                "  |     ^",
+                "  |",
+                "  = remarque: Prefix it with an underscore",
+            ])
+        );
+        assert_eq!(error.message(), "unused variable `x`");
+    }
+
     #[test]
     fn test_basic_error() {
         let message = "An error occurred".to_string();
         basic_error(message);
     }
+
+    #[test]
+    fn test_render_colored() {
+        let source = Source::from_str("test.rs", "let x = 10");
+        let error = Error::new("Syntax error".to_string(), &source, Span::new(4, 5));
+        let config = RenderConfig {
+            color: ColorChoice::Always,
+            ..Default::default()
+        };
+
+        let mut output = String::new();
+        error.render(&mut output, &config).unwrap();
+        assert!(output.starts_with("\x1b[1;31merror\x1b[0m"));
+
+        let mut output = Vec::new();
+        error.write_to(&mut output, &RenderConfig::plain()).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), error.to_string());
+    }
 }
@@ -0,0 +1,153 @@
+//! This module defines the `ExpansionTable`, which records where macro-expanded and included text came from.
+//!
+//! Preprocessors that splice text into a new `Source` register an `Expansion` for it,
+//! pointing back at the call site in the parent source. Errors in the expanded text can then
+//! walk the chain of call sites to show where the text originated.
+
+use crate::{source::Source, span::Span};
+
+/// Identifies an `Expansion` within an `ExpansionTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExpansionId(usize);
+
+/// Describes what produced an expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpansionKind {
+    /// Text produced by expanding the named macro.
+    Macro(String),
+    /// Text spliced in from the named file.
+    Include(String),
+}
+
+/// Represents a single expansion: synthetic text and the call site that produced it.
+#[derive(Debug)]
+pub struct Expansion<'a> {
+    /// What produced the expansion.
+    pub kind: ExpansionKind,
+    /// The source holding the expanded text.
+    pub source: &'a Source<'a>,
+    /// The source containing the call site.
+    pub call_source: &'a Source<'a>,
+    /// The span of the invocation (or include directive) in `call_source`.
+    pub call_site: Span,
+    /// The expansion that `call_source` belongs to, if it is itself expanded text.
+    pub parent: Option<ExpansionId>,
+}
+
+/// Records expansions so that spans in expanded text can be traced back to their call sites.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     expansion::{ExpansionKind, ExpansionTable},
+///     source::Source,
+///     span::Span,
+/// };
+///
+/// let main = Source::from_str("main.c", "int x = SQUARE(2);");
+/// let expanded = Source::from_str("<macro SQUARE>", "2 * 2");
+///
+/// let mut table = ExpansionTable::new();
+/// let id = table.push(
+///     ExpansionKind::Macro("SQUARE".to_string()),
+///     &expanded,
+///     &main,
+///     Span::new(8, 17),
+///     None,
+/// );
+///
+/// assert_eq!(table.backtrace(id).count(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct ExpansionTable<'a> {
+    /// The recorded expansions, indexed by `ExpansionId`.
+    expansions: Vec<Expansion<'a>>,
+}
+
+impl<'a> ExpansionTable<'a> {
+    /// Creates a new, empty `ExpansionTable`.
+    pub fn new() -> Self {
+        ExpansionTable {
+            expansions: Vec::new(),
+        }
+    }
+
+    /// Records a new expansion and returns its id.
+    ///
+    /// `parent` should be the expansion that `call_source` belongs to, or `None` if the call
+    /// site is in ordinary (unexpanded) source code.
+    pub fn push(
+        &mut self,
+        kind: ExpansionKind,
+        source: &'a Source<'a>,
+        call_source: &'a Source<'a>,
+        call_site: Span,
+        parent: Option<ExpansionId>,
+    ) -> ExpansionId {
+        let id = ExpansionId(self.expansions.len());
+        self.expansions.push(Expansion {
+            kind,
+            source,
+            call_source,
+            call_site,
+            parent,
+        });
+        id
+    }
+
+    /// Returns the expansion with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id does not belong to this table.
+    pub fn get(&self, id: ExpansionId) -> &Expansion<'a> {
+        &self.expansions[id.0]
+    }
+
+    /// Returns the expansions from the given one outwards, ending with the one
+    /// whose call site is in ordinary source code.
+    pub fn backtrace(&self, id: ExpansionId) -> impl Iterator<Item = &Expansion<'a>> {
+        let mut next = Some(id);
+        std::iter::from_fn(move || {
+            let expansion = self.get(next?);
+            next = expansion.parent;
+            Some(expansion)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backtrace() {
+        let main = Source::from_str("main.c", "#include \"a.h\"");
+        let header = Source::from_str("a.h", "FOO(1)");
+        let expanded = Source::from_str("<macro FOO>", "1 + 1");
+
+        let mut table = ExpansionTable::new();
+        let include = table.push(
+            ExpansionKind::Include("a.h".to_string()),
+            &header,
+            &main,
+            Span::new(0, 14),
+            None,
+        );
+        let foo = table.push(
+            ExpansionKind::Macro("FOO".to_string()),
+            &expanded,
+            &header,
+            Span::new(0, 6),
+            Some(include),
+        );
+
+        let chain = table.backtrace(foo).collect::<Vec<_>>();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].kind, ExpansionKind::Macro("FOO".to_string()));
+        assert_eq!(chain[0].call_source.filename, "a.h");
+        assert_eq!(chain[1].kind, ExpansionKind::Include("a.h".to_string()));
+        assert_eq!(chain[1].call_source.filename, "main.c");
+    }
+}
@@ -0,0 +1,136 @@
+//! This module provides utilities for applying the suggestions attached to errors.
+//!
+//! This is the building block for a `--fix` mode: collect the errors for a source, then
+//! apply every suggestion that is safe to apply automatically.
+
+use crate::{
+    error::{Applicability, Error, Suggestion},
+    source::Source,
+};
+
+/// Applies the given suggestions to the code, returning the fixed code.
+///
+/// Suggestions are applied in order of position. A suggestion that overlaps one that was already
+/// applied is skipped, as is an exact duplicate of one.
+///
+/// # Panics
+///
+/// Panics if a suggestion is out of bounds or does not lie on UTF-8 character boundaries.
+pub fn apply<'s>(code: &str, suggestions: impl IntoIterator<Item = &'s Suggestion>) -> String {
+    let mut suggestions = suggestions.into_iter().collect::<Vec<&Suggestion>>();
+    suggestions.sort_by_key(|suggestion| suggestion.span);
+
+    let mut fixed = String::with_capacity(code.len());
+    let mut position = 0;
+    let mut previous: Option<&Suggestion> = None;
+
+    for suggestion in suggestions {
+        if let Some(previous) = previous {
+            let overlaps = suggestion.span.start < previous.span.end
+                || (suggestion.span.is_empty() && suggestion.span.start == previous.span.start);
+            if overlaps {
+                continue;
+            }
+        }
+
+        fixed += &code[position..suggestion.span.start];
+        fixed += &suggestion.replacement;
+        position = suggestion.span.end;
+        previous = Some(suggestion);
+    }
+
+    fixed += &code[position..];
+    fixed
+}
+
+/// Applies every machine-applicable suggestion of the given errors to the source, returning the fixed code.
+///
+/// Only errors that occurred in `source` (compared by filename) are considered.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     error::{Applicability, Error},
+///     fix::apply_suggestions,
+///     source::Source,
+///     span::Span,
+/// };
+///
+/// let source = Source::from_str("main.rn", "let x = 10");
+/// let errors = vec![
+///     Error::new("expected `;`".to_string(), &source, Span::empty(10)).with_suggestion(
+///         "add `;` here".to_string(),
+///         Span::empty(10),
+///         ";".to_string(),
+///         Applicability::MachineApplicable,
+///     ),
+/// ];
+///
+/// assert_eq!(apply_suggestions(&source, &errors), "let x = 10;");
+/// ```
+pub fn apply_suggestions(source: &Source, errors: &[Error]) -> String {
+    let suggestions = errors
+        .iter()
+        .filter(|error| error.source_file().filename == source.filename)
+        .flat_map(|error| error.helps())
+        .filter_map(|help| help.suggestion.as_ref())
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable);
+
+    apply(&source.code, suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Span;
+
+    fn suggestion(start: usize, end: usize, replacement: &str) -> Suggestion {
+        Suggestion {
+            span: Span::new(start, end),
+            replacement: replacement.to_string(),
+            applicability: Applicability::MachineApplicable,
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let code = "let x = 10";
+        let suggestions = [
+            suggestion(10, 10, ";"),
+            suggestion(4, 5, "_x"),
+            suggestion(4, 6, "y "),
+            suggestion(10, 10, ";"),
+        ];
+
+        assert_eq!(apply(code, &suggestions), "let _x = 10;");
+    }
+
+    #[test]
+    fn test_apply_suggestions() {
+        let source = Source::from_str("test.rs", "let x = 10");
+        let other = Source::from_str("other.rs", "let x = 10");
+        let errors = vec![
+            Error::new("Syntax error".to_string(), &source, Span::empty(10)).with_suggestion(
+                "Add a semicolon".to_string(),
+                Span::empty(10),
+                ";".to_string(),
+                Applicability::MachineApplicable,
+            ),
+            Error::new("Unused variable".to_string(), &source, Span::new(4, 5)).with_suggestion(
+                "Rename it".to_string(),
+                Span::new(4, 5),
+                "<name>".to_string(),
+                Applicability::HasPlaceholders,
+            ),
+            Error::new("Syntax error".to_string(), &other, Span::new(0, 3)).with_suggestion(
+                "Remove it".to_string(),
+                Span::new(0, 4),
+                String::new(),
+                Applicability::MachineApplicable,
+            ),
+        ];
+
+        assert_eq!(apply_suggestions(&source, &errors), "let x = 10;");
+    }
+}
@@ -0,0 +1,675 @@
+//! This module provides an analysis of context-free grammars described as Rust data.
+//!
+//! Runic parsers are written by hand, but describing their grammar as productions over token
+//! kinds lets this module compute FIRST and FOLLOW sets and check that the grammar suits a
+//! recursive descent parser with one token of lookahead: it reports LL(1) conflicts, left
+//! recursion, undefined rules, a start rule without productions and unreachable rules as
+//! diagnostics.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error as StdError,
+    fmt,
+};
+
+use crate::{
+    diagnostics::Diagnostics,
+    error::{Error, Severity},
+    source::Source,
+    span::Span,
+    token::{TokenKind, describe_alternatives},
+};
+
+/// A symbol on the right-hand side of a production.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Term<T> {
+    /// A token of the given kind.
+    Token(T),
+    /// A rule of the grammar, by name.
+    Rule(String),
+}
+
+impl<T> Term<T> {
+    /// Creates a new `Term` matching a token of the given kind.
+    pub fn token(kind: T) -> Self {
+        Term::Token(kind)
+    }
+
+    /// Creates a new `Term` matching the rule with the given name.
+    pub fn rule(name: &str) -> Self {
+        Term::Rule(name.to_string())
+    }
+}
+
+/// A production of a grammar, such as `expr -> term "+" expr`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Production<T> {
+    /// The name of the rule the production belongs to.
+    pub rule: String,
+    /// The symbols of the production. An empty production matches nothing.
+    pub terms: Vec<Term<T>>,
+}
+
+/// A token that can follow a rule: a token kind, or the end of the input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Lookahead<T> {
+    /// A token of the given kind.
+    Token(T),
+    /// The end of the input.
+    End,
+}
+
+impl<T: TokenKind> Lookahead<T> {
+    /// Returns a description of the lookahead, such as "`+`" or "end of input".
+    pub fn describe(&self) -> String {
+        match self {
+            Lookahead::Token(kind) => kind.describe(),
+            Lookahead::End => "end of input".to_string(),
+        }
+    }
+
+    /// Returns `true` if both lookaheads are the end of the input, or tokens of the same kind.
+    fn same_kind(&self, other: &Lookahead<T>) -> bool {
+        match (self, other) {
+            (Lookahead::Token(a), Lookahead::Token(b)) => a.same_kind(b),
+            (Lookahead::End, Lookahead::End) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The error returned when checking a grammar against a source that does not hold its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MismatchedSource;
+
+impl fmt::Display for MismatchedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the source does not hold the text of the grammar")
+    }
+}
+
+impl StdError for MismatchedSource {}
+
+/// A context-free grammar, made of productions over token kinds.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{diagnostics::Diagnostics, grammar::{Grammar, Term}};
+///
+/// let grammar = Grammar::new("expr")
+///     .with_production("expr", vec![Term::rule("expr"), Term::token("+"), Term::token("int")])
+///     .with_production("expr", vec![Term::token("int")]);
+///
+/// let analysis = grammar.analyze();
+/// assert_eq!(analysis.first("expr"), ["int"]);
+///
+/// let source = grammar.to_source("grammar");
+/// let mut diagnostics = Diagnostics::new();
+/// grammar.check(&source, &mut diagnostics).unwrap();
+/// assert!(diagnostics.has_errors());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Grammar<T> {
+    /// The name of the start rule.
+    start: String,
+    /// The productions of the grammar, in order.
+    productions: Vec<Production<T>>,
+}
+
+impl<T: TokenKind + Clone> Grammar<T> {
+    /// Creates a new `Grammar` without productions, whose start rule has the given name.
+    pub fn new(start: &str) -> Self {
+        Grammar {
+            start: start.to_string(),
+            productions: Vec::new(),
+        }
+    }
+
+    /// Creates a new `Grammar`, adding a production to the rule with the given name.
+    pub fn with_production(mut self, rule: &str, terms: Vec<Term<T>>) -> Self {
+        self.productions.push(Production {
+            rule: rule.to_string(),
+            terms,
+        });
+        self
+    }
+
+    /// Returns the name of the start rule.
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    /// Returns the productions of the grammar, in order.
+    pub fn productions(&self) -> &[Production<T>] {
+        &self.productions
+    }
+
+    /// Returns the names of the rules with at least one production, in order of first appearance.
+    pub fn rules(&self) -> Vec<&str> {
+        let mut rules: Vec<&str> = Vec::new();
+        for production in &self.productions {
+            if !rules.contains(&production.rule.as_str()) {
+                rules.push(&production.rule);
+            }
+        }
+        rules
+    }
+
+    /// Computes the nullable rules, and the FIRST and FOLLOW sets of every rule.
+    pub fn analyze(&self) -> Analysis<T> {
+        let mut analysis = Analysis {
+            nullable: HashSet::new(),
+            first: HashMap::new(),
+            follow: HashMap::new(),
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.productions {
+                let (first, nullable) = analysis.first_of(&production.terms);
+                let set = analysis.first.entry(production.rule.clone()).or_default();
+                for kind in first {
+                    changed |= insert(set, kind, |a, b| a.same_kind(b));
+                }
+                if nullable {
+                    changed |= analysis.nullable.insert(production.rule.clone());
+                }
+            }
+        }
+
+        analysis
+            .follow
+            .entry(self.start.clone())
+            .or_default()
+            .push(Lookahead::End);
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.productions {
+                for (index, term) in production.terms.iter().enumerate() {
+                    let Term::Rule(name) = term else {
+                        continue;
+                    };
+
+                    let (first, nullable) = analysis.first_of(&production.terms[index + 1..]);
+                    let mut follow: Vec<Lookahead<T>> =
+                        first.into_iter().map(Lookahead::Token).collect();
+                    if nullable {
+                        follow.extend(analysis.follow(&production.rule).iter().cloned());
+                    }
+
+                    let set = analysis.follow.entry(name.clone()).or_default();
+                    for lookahead in follow {
+                        changed |= insert(set, lookahead, Lookahead::same_kind);
+                    }
+                }
+            }
+        }
+
+        analysis
+    }
+
+    /// Creates a `Source` holding the grammar, one production per line, to report diagnostics in.
+    pub fn to_source<'a>(&self, filename: &'a str) -> Source<'a> {
+        Source::from_str(filename, &self.to_string())
+    }
+
+    /// Checks that the grammar is LL(1), pushing an error for every problem found.
+    ///
+    /// The errors point at the productions in `source`, which must come from `to_source`.
+    /// A start rule without productions, undefined rules, left recursion, and productions of
+    /// a rule that can start with the same token are reported as errors, and rules that cannot
+    /// be reached from the start rule as warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MismatchedSource`, without checking anything, if `source` does not hold the
+    /// text of the grammar.
+    pub fn check<'a>(
+        &self,
+        source: &'a Source<'a>,
+        diagnostics: &mut Diagnostics<'a>,
+    ) -> Result<(), MismatchedSource> {
+        if source.code != self.to_string() {
+            return Err(MismatchedSource);
+        }
+
+        let spans = self.production_spans();
+        let analysis = self.analyze();
+        let rules = self.rules();
+
+        if !rules.contains(&self.start.as_str()) {
+            diagnostics.push(
+                Error::new(
+                    format!("start rule `{}` has no productions", self.start),
+                    source,
+                    Span::empty(0),
+                )
+                .with_help(format!("add a production to `{}`", self.start)),
+            );
+        }
+
+        for (production, span) in self.productions.iter().zip(&spans) {
+            for term in &production.terms {
+                if let Term::Rule(name) = term
+                    && !rules.contains(&name.as_str())
+                {
+                    diagnostics.push(
+                        Error::new(format!("undefined rule `{}`", name), source, *span)
+                            .with_primary_label(format!("`{}` has no productions", name)),
+                    );
+                }
+            }
+        }
+
+        if rules.contains(&self.start.as_str()) {
+            let reachable = self.reachable_rules();
+            for rule in rules.iter().filter(|rule| !reachable.contains(**rule)) {
+                let index = self
+                    .productions
+                    .iter()
+                    .position(|production| production.rule == *rule)
+                    .expect("every rule has a production");
+                diagnostics.push(
+                    Error::new(format!("unreachable rule `{}`", rule), source, spans[index])
+                        .with_severity(Severity::Warning)
+                        .with_primary_label(format!(
+                            "`{}` cannot be reached from the start rule `{}`",
+                            rule, self.start
+                        )),
+                );
+            }
+        }
+
+        let mut reported = HashSet::new();
+        for rule in &rules {
+            if reported.contains(*rule) {
+                continue;
+            }
+            if let Some(cycle) = self.left_recursion(rule, &analysis) {
+                let (first, _) = cycle[0];
+                let path = cycle
+                    .iter()
+                    .map(|(index, _)| format!("`{}`", self.productions[*index].rule))
+                    .chain(std::iter::once(format!("`{}`", rule)))
+                    .collect::<Vec<String>>()
+                    .join(" -> ");
+
+                let mut error = Error::new(
+                    format!("left recursion in rule `{}`", rule),
+                    source,
+                    spans[first],
+                )
+                .with_note(format!("the recursion is {}", path))
+                .with_help(
+                    "rewrite the rule with a loop, or parse it with a `PrattParser`".to_string(),
+                );
+                for (index, _) in &cycle[1..] {
+                    error = error.with_label(spans[*index], "continues here".to_string());
+                }
+                diagnostics.push(error);
+
+                reported.extend(cycle.iter().map(|(_, name)| name.clone()));
+            }
+        }
+
+        for rule in &rules {
+            let productions = self
+                .productions
+                .iter()
+                .enumerate()
+                .filter(|(_, production)| production.rule == *rule)
+                .map(|(index, production)| (index, analysis.predict(production)))
+                .collect::<Vec<_>>();
+
+            for (i, (first, first_predict)) in productions.iter().enumerate() {
+                for (second, second_predict) in &productions[i + 1..] {
+                    let common = first_predict
+                        .iter()
+                        .filter(|a| second_predict.iter().any(|b| a.same_kind(b)))
+                        .map(Lookahead::describe)
+                        .collect::<Vec<String>>();
+                    if common.is_empty() {
+                        continue;
+                    }
+
+                    let common = describe_alternatives(common);
+                    diagnostics.push(
+                        Error::new(
+                            format!("LL(1) conflict in rule `{}`", rule),
+                            source,
+                            spans[*second],
+                        )
+                        .with_primary_label(format!(
+                            "this production can also start with {}",
+                            common
+                        ))
+                        .with_label(
+                            spans[*first],
+                            format!("this production can start with {}", common),
+                        ),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the span of every production in the text of the grammar.
+    fn production_spans(&self) -> Vec<Span> {
+        let mut offset = 0;
+        self.productions
+            .iter()
+            .map(|production| {
+                let len = ProductionDisplay(production).to_string().len();
+                let span = Span::new(offset, offset + len);
+                offset += len + 1;
+                span
+            })
+            .collect()
+    }
+
+    /// Returns the names of the rules reachable from the start rule, including it.
+    fn reachable_rules(&self) -> HashSet<&str> {
+        let mut reachable = HashSet::from([self.start.as_str()]);
+        let mut pending = vec![self.start.as_str()];
+        while let Some(rule) = pending.pop() {
+            for production in self.productions.iter().filter(|p| p.rule == rule) {
+                for term in &production.terms {
+                    if let Term::Rule(name) = term
+                        && reachable.insert(name)
+                    {
+                        pending.push(name);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Finds a left-recursive cycle through `rule`, as the productions taken and the rules they
+    /// belong to, starting with a production of `rule`.
+    fn left_recursion(&self, rule: &str, analysis: &Analysis<T>) -> Option<Vec<(usize, String)>> {
+        let mut path = Vec::new();
+        let mut visited = HashSet::new();
+        self.find_left_cycle(rule, rule, analysis, &mut path, &mut visited)
+            .then_some(path)
+    }
+
+    /// Searches for `target` among the rules `current` can start with, recording the path taken.
+    fn find_left_cycle(
+        &self,
+        target: &str,
+        current: &str,
+        analysis: &Analysis<T>,
+        path: &mut Vec<(usize, String)>,
+        visited: &mut HashSet<String>,
+    ) -> bool {
+        if !visited.insert(current.to_string()) {
+            return false;
+        }
+
+        for (index, production) in self.productions.iter().enumerate() {
+            if production.rule != current {
+                continue;
+            }
+
+            for term in &production.terms {
+                let name = match term {
+                    Term::Token(_) => break,
+                    Term::Rule(name) => name,
+                };
+
+                path.push((index, current.to_string()));
+                if name == target || self.find_left_cycle(target, name, analysis, path, visited) {
+                    return true;
+                }
+                path.pop();
+
+                if !analysis.is_nullable(name) {
+                    break;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+impl<T: TokenKind> fmt::Display for Grammar<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, production) in self.productions.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", ProductionDisplay(production))?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays a production as `rule -> terms`, with `ε` for an empty production.
+struct ProductionDisplay<'p, T>(&'p Production<T>);
+
+impl<T: TokenKind> fmt::Display for ProductionDisplay<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ->", self.0.rule)?;
+        if self.0.terms.is_empty() {
+            return write!(f, " ε");
+        }
+        for term in &self.0.terms {
+            match term {
+                Term::Token(kind) => write!(f, " {}", kind.describe())?,
+                Term::Rule(name) => write!(f, " {}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The nullable rules, and the FIRST and FOLLOW sets of the rules of a grammar.
+#[derive(Debug, Clone)]
+pub struct Analysis<T> {
+    /// The rules that can match nothing.
+    nullable: HashSet<String>,
+    /// The kinds of the tokens each rule can start with.
+    first: HashMap<String, Vec<T>>,
+    /// The tokens that can follow each rule.
+    follow: HashMap<String, Vec<Lookahead<T>>>,
+}
+
+impl<T: TokenKind + Clone> Analysis<T> {
+    /// Returns `true` if the rule can match nothing.
+    pub fn is_nullable(&self, rule: &str) -> bool {
+        self.nullable.contains(rule)
+    }
+
+    /// Returns the kinds of the tokens the rule can start with.
+    pub fn first(&self, rule: &str) -> &[T] {
+        self.first.get(rule).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the tokens that can follow the rule.
+    pub fn follow(&self, rule: &str) -> &[Lookahead<T>] {
+        self.follow.get(rule).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the tokens that select the production in a recursive descent parser: the tokens
+    /// it can start with, and the tokens following its rule if it can match nothing.
+    pub fn predict(&self, production: &Production<T>) -> Vec<Lookahead<T>> {
+        let (first, nullable) = self.first_of(&production.terms);
+        let mut predict: Vec<Lookahead<T>> = first.into_iter().map(Lookahead::Token).collect();
+        if nullable {
+            for lookahead in self.follow(&production.rule) {
+                insert(&mut predict, lookahead.clone(), Lookahead::same_kind);
+            }
+        }
+        predict
+    }
+
+    /// Returns the kinds of the tokens a sequence of terms can start with, and whether it can
+    /// match nothing.
+    fn first_of(&self, terms: &[Term<T>]) -> (Vec<T>, bool) {
+        let mut first = Vec::new();
+        for term in terms {
+            match term {
+                Term::Token(kind) => {
+                    insert(&mut first, kind.clone(), |a, b| a.same_kind(b));
+                    return (first, false);
+                }
+                Term::Rule(name) => {
+                    for kind in self.first(name) {
+                        insert(&mut first, kind.clone(), |a, b| a.same_kind(b));
+                    }
+                    if !self.is_nullable(name) {
+                        return (first, false);
+                    }
+                }
+            }
+        }
+        (first, true)
+    }
+}
+
+/// Adds the item to the set unless an equivalent one is already there, returning `true` if added.
+fn insert<I>(set: &mut Vec<I>, item: I, same: impl Fn(&I, &I) -> bool) -> bool {
+    if set.iter().any(|existing| same(existing, &item)) {
+        return false;
+    }
+    set.push(item);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::HasSpan;
+
+    /// `expr -> term expr' ; expr' -> "+" term expr' | ε ; term -> "int" | "(" expr ")"`
+    fn expression_grammar() -> Grammar<&'static str> {
+        Grammar::new("expr")
+            .with_production("expr", vec![Term::rule("term"), Term::rule("rest")])
+            .with_production(
+                "rest",
+                vec![Term::token("+"), Term::rule("term"), Term::rule("rest")],
+            )
+            .with_production("rest", vec![])
+            .with_production("term", vec![Term::token("int")])
+            .with_production(
+                "term",
+                vec![Term::token("("), Term::rule("expr"), Term::token(")")],
+            )
+    }
+
+    #[test]
+    fn test_first_and_follow_sets() {
+        let grammar = expression_grammar();
+        let analysis = grammar.analyze();
+
+        assert_eq!(analysis.first("expr"), ["int", "("]);
+        assert_eq!(analysis.first("rest"), ["+"]);
+        assert!(analysis.is_nullable("rest"));
+        assert!(!analysis.is_nullable("expr"));
+
+        assert_eq!(
+            analysis.follow("expr"),
+            [Lookahead::End, Lookahead::Token(")")]
+        );
+        assert_eq!(
+            analysis.follow("term"),
+            [Lookahead::Token("+"), Lookahead::End, Lookahead::Token(")")]
+        );
+        assert_eq!(
+            analysis.predict(&grammar.productions()[2]),
+            [Lookahead::End, Lookahead::Token(")")]
+        );
+    }
+
+    #[test]
+    fn test_check_accepts_ll1_grammar() {
+        let grammar = expression_grammar();
+        let source = grammar.to_source("grammar");
+        let mut diagnostics = Diagnostics::new();
+        grammar.check(&source, &mut diagnostics).unwrap();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(source.code.lines().nth(2), Some("rest -> ε"));
+    }
+
+    #[test]
+    fn test_check_reports_problems() {
+        let grammar = Grammar::new("stmt")
+            .with_production("stmt", vec![Term::token("let"), Term::rule("name")])
+            .with_production("stmt", vec![Term::token("let"), Term::token("mut")])
+            .with_production("stmt", vec![Term::rule("expr")])
+            .with_production("expr", vec![Term::rule("call"), Term::token("int")])
+            .with_production("call", vec![Term::rule("expr"), Term::token("(")]);
+        let source = grammar.to_source("grammar");
+        let mut diagnostics = Diagnostics::new();
+        grammar.check(&source, &mut diagnostics).unwrap();
+
+        let errors = diagnostics.iter().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3);
+
+        assert_eq!(errors[0].message(), "undefined rule `name`");
+        assert_eq!(errors[0].span(), Span::new(0, 18));
+
+        assert_eq!(errors[1].message(), "left recursion in rule `expr`");
+        assert_eq!(
+            errors[1].notes(),
+            ["the recursion is `expr` -> `call` -> `expr`"]
+        );
+
+        assert_eq!(errors[2].message(), "LL(1) conflict in rule `stmt`");
+        assert_eq!(
+            errors[2].labels()[0].message.as_deref(),
+            Some("this production can also start with `let`")
+        );
+        assert_eq!(errors[2].labels()[1].span, Span::new(0, 18));
+    }
+
+    #[test]
+    fn test_check_reports_start_and_unreachable_rules() {
+        let grammar = Grammar::new("program").with_production("expr", vec![Term::token("int")]);
+        let source = grammar.to_source("grammar");
+        let mut diagnostics = Diagnostics::new();
+        grammar.check(&source, &mut diagnostics).unwrap();
+
+        let errors = diagnostics.iter().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message(),
+            "start rule `program` has no productions"
+        );
+
+        let grammar = expression_grammar().with_production("stray", vec![Term::token("int")]);
+        let source = grammar.to_source("grammar");
+        let mut diagnostics = Diagnostics::new();
+        grammar.check(&source, &mut diagnostics).unwrap();
+
+        let errors = diagnostics.iter().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message(), "unreachable rule `stray`");
+        assert_eq!(errors[0].severity(), Severity::Warning);
+        let span = errors[0].span();
+        assert_eq!(&source.code[span.start..span.end], "stray -> `int`");
+        assert!(!diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_check_rejects_other_sources() {
+        let grammar = expression_grammar();
+        let source = Source::from_str("grammar", "expr -> int");
+        let mut diagnostics = Diagnostics::new();
+
+        assert_eq!(
+            grammar.check(&source, &mut diagnostics),
+            Err(MismatchedSource)
+        );
+        assert!(diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+//! This module defines the `Interner` struct, which maps strings to cheap `Symbol` handles.
+//!
+//! Interning identifiers and literals once in the lexer lets later phases copy and compare
+//! them in constant time instead of handling strings.
+
+use std::collections::HashMap;
+
+/// A handle to a string stored in an `Interner`.
+///
+/// Symbols from the same interner are equal if and only if their strings are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the index of the symbol in its interner.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Stores interned strings.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::intern::Interner;
+///
+/// let mut interner = Interner::new();
+/// let a = interner.intern("main");
+/// let b = interner.intern("main");
+///
+/// assert_eq!(a, b);
+/// assert_eq!(interner.resolve(a), "main");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    /// The symbols of the interned strings.
+    symbols: HashMap<String, Symbol>,
+    /// The interned strings, by symbol index.
+    strings: Vec<String>,
+}
+
+impl Interner {
+    /// Creates a new, empty `Interner`.
+    pub fn new() -> Self {
+        Interner {
+            symbols: HashMap::new(),
+            strings: Vec::new(),
+        }
+    }
+
+    /// Interns the string, returning its symbol.
+    ///
+    /// Interning the same string again returns the same symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` strings are interned.
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(string) {
+            return *symbol;
+        }
+
+        let index = u32::try_from(self.strings.len()).expect("too many interned strings");
+        let symbol = Symbol(index);
+        self.strings.push(string.to_string());
+        self.symbols.insert(string.to_string(), symbol);
+        symbol
+    }
+
+    /// Returns the symbol of the string, if it has been interned.
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.symbols.get(string).copied()
+    }
+
+    /// Returns the string of the symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbol does not come from this interner.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.index()]
+    }
+
+    /// Returns the number of interned strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+        let x = interner.intern("x");
+        let y = interner.intern("y");
+
+        assert_ne!(x, y);
+        assert_eq!(interner.intern("x"), x);
+        assert_eq!(interner.get("y"), Some(y));
+        assert_eq!(interner.get("z"), None);
+        assert_eq!(interner.resolve(y), "y");
+        assert_eq!(interner.len(), 2);
+    }
+}
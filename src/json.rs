@@ -0,0 +1,491 @@
+//! This module provides machine-readable output for diagnostics.
+//!
+//! Errors can be converted to a JSON object similar to rustc's `--error-format=json`,
+//! and batches of errors can be converted to a SARIF 2.1 log for code-scanning tools.
+
+use std::fmt;
+
+use crate::{
+    error::{Error, LabelStyle, Severity},
+    render::percent_encode,
+    source::Source,
+    span::{HasSpan, Span, location_to_line_col},
+};
+
+/// Represents a JSON value.
+///
+/// Objects keep their keys in insertion order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    /// The `null` value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A non-negative integer.
+    Number(usize),
+    /// A string.
+    String(String),
+    /// An array of values.
+    Array(Vec<Json>),
+    /// An object, as a list of key-value pairs.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Creates a JSON object from the given key-value pairs.
+    pub fn object<const N: usize>(pairs: [(&str, Json); N]) -> Self {
+        Json::Object(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Returns the value for the given key, if `self` is an object containing it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl fmt::Display for Json {
+    /// Writes the value as compact JSON.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(pairs) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Writes a string as a quoted and escaped JSON string.
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Converts a span to a JSON object with its file, byte range and line/column range.
+///
+/// Lines and columns are 1-based, and the end column is exclusive.
+fn span_to_json(source: &Source, span: Span) -> Vec<(String, Json)> {
+    let (line_start, column_start) = location_to_line_col(&source.code, span.start);
+    let (line_end, column_end) = location_to_line_col(&source.code, span.end);
+
+    vec![
+        ("file_name".to_string(), source.filename.into()),
+        ("byte_start".to_string(), span.start.into()),
+        ("byte_end".to_string(), span.end.into()),
+        ("line_start".to_string(), line_start.into()),
+        ("line_end".to_string(), line_end.into()),
+        ("column_start".to_string(), column_start.into()),
+        ("column_end".to_string(), column_end.into()),
+    ]
+}
+
+impl Error<'_> {
+    /// Converts the error to a JSON object.
+    ///
+    /// The object contains the message, severity, code, labels (with byte and line/column ranges),
+    /// context, notes, help entries with their suggestions, and the plain rendered text.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{error::Error, source::Source, span::Span};
+    ///
+    /// let source = Source::from_str("main.rn", "let x = 10");
+    /// let error = Error::new("expected `;`".to_string(), &source, Span::empty(10));
+    ///
+    /// let json = error.to_json().to_string();
+    /// assert!(json.starts_with(r#"{"message":"expected `;`","severity":"error""#));
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let source = self.source_file();
+
+        let labels = self
+            .labels()
+            .iter()
+            .map(|label| {
+                let mut pairs = span_to_json(source, label.span);
+                pairs.push((
+                    "is_primary".to_string(),
+                    (label.style == LabelStyle::Primary).into(),
+                ));
+                pairs.push(("label".to_string(), label.message.clone().into()));
+                Json::Object(pairs)
+            })
+            .collect();
+
+        let helps = self
+            .helps()
+            .iter()
+            .map(|help| {
+                let suggestion = help.suggestion.as_ref().map_or(Json::Null, |suggestion| {
+                    let mut pairs = span_to_json(source, suggestion.span);
+                    pairs.push((
+                        "replacement".to_string(),
+                        suggestion.replacement.clone().into(),
+                    ));
+                    pairs.push((
+                        "applicability".to_string(),
+                        suggestion.applicability.as_str().into(),
+                    ));
+                    Json::Object(pairs)
+                });
+                Json::object([
+                    ("message", help.message.clone().into()),
+                    ("suggestion", suggestion),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("message", self.message().into()),
+            ("severity", self.severity().as_str().into()),
+            ("code", self.code().into()),
+            ("file", source.filename.into()),
+            ("labels", Json::Array(labels)),
+            (
+                "context",
+                Json::Array(self.context().iter().map(|c| c.as_str().into()).collect()),
+            ),
+            (
+                "notes",
+                Json::Array(self.notes().iter().map(|n| n.as_str().into()).collect()),
+            ),
+            ("helps", Json::Array(helps)),
+            ("rendered", self.to_string().into()),
+        ])
+    }
+}
+
+/// Converts a span to the key-value pairs of a SARIF location, holding its `physicalLocation`.
+///
+/// Columns are counted in Unicode code points, as declared by the `columnKind` of the run.
+fn sarif_location(source: &Source, span: Span) -> Vec<(String, Json)> {
+    let (start_line, start_column) = location_to_line_col(&source.code, span.start);
+    let (end_line, end_column) = location_to_line_col(&source.code, span.end);
+
+    vec![(
+        "physicalLocation".to_string(),
+        Json::object([
+            ("artifactLocation", sarif_artifact(source)),
+            (
+                "region",
+                Json::object([
+                    ("startLine", start_line.into()),
+                    ("startColumn", start_column.into()),
+                    ("endLine", end_line.into()),
+                    ("endColumn", end_column.into()),
+                    ("byteOffset", span.start.into()),
+                    ("byteLength", span.len().into()),
+                ]),
+            ),
+        ]),
+    )]
+}
+
+/// Converts a source to a SARIF `artifactLocation` object, with its filename as a URI.
+fn sarif_artifact(source: &Source) -> Json {
+    Json::object([("uri", percent_encode(source.filename).into())])
+}
+
+/// Converts a batch of errors to a SARIF 2.1 log with a single run of the named tool.
+///
+/// Error codes become rule ids, secondary labels become related locations,
+/// and suggestions become fixes.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{error::Error, json::sarif, source::Source, span::Span};
+///
+/// let source = Source::from_str("main.rn", "let x = 10");
+/// let errors = vec![Error::new("expected `;`".to_string(), &source, Span::empty(10))];
+///
+/// let log = sarif(&errors, "runic", "0.0.1");
+/// assert!(log.to_string().contains(r#""version":"2.1.0""#));
+/// ```
+pub fn sarif(errors: &[Error], tool_name: &str, tool_version: &str) -> Json {
+    let mut rules: Vec<&str> = errors.iter().filter_map(|error| error.code()).collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results = errors
+        .iter()
+        .map(|error| {
+            let source = error.source_file();
+            let level = match error.severity() {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info | Severity::Note | Severity::Help => "note",
+            };
+
+            let related = error
+                .labels()
+                .iter()
+                .filter(|label| label.style == LabelStyle::Secondary)
+                .enumerate()
+                .map(|(index, label)| {
+                    let mut pairs = sarif_location(source, label.span);
+                    pairs.insert(0, ("id".to_string(), index.into()));
+                    if let Some(message) = &label.message {
+                        pairs.push((
+                            "message".to_string(),
+                            Json::object([("text", message.as_str().into())]),
+                        ));
+                    }
+                    Json::Object(pairs)
+                })
+                .collect();
+
+            let fixes = error
+                .helps()
+                .iter()
+                .filter_map(|help| {
+                    let suggestion = help.suggestion.as_ref()?;
+                    Some(Json::object([
+                        (
+                            "description",
+                            Json::object([("text", help.message.as_str().into())]),
+                        ),
+                        (
+                            "artifactChanges",
+                            Json::Array(vec![Json::object([
+                                ("artifactLocation", sarif_artifact(source)),
+                                (
+                                    "replacements",
+                                    Json::Array(vec![Json::object([
+                                        (
+                                            "deletedRegion",
+                                            Json::object([
+                                                ("byteOffset", suggestion.span.start.into()),
+                                                ("byteLength", suggestion.span.len().into()),
+                                            ]),
+                                        ),
+                                        (
+                                            "insertedContent",
+                                            Json::object([(
+                                                "text",
+                                                suggestion.replacement.as_str().into(),
+                                            )]),
+                                        ),
+                                    ])]),
+                                ),
+                            ])]),
+                        ),
+                    ]))
+                })
+                .collect();
+
+            let mut pairs = Vec::new();
+            if let Some(code) = error.code() {
+                pairs.push(("ruleId".to_string(), code.into()));
+            }
+            pairs.push(("level".to_string(), level.into()));
+            pairs.push((
+                "message".to_string(),
+                Json::object([("text", error.message().into())]),
+            ));
+            pairs.push((
+                "locations".to_string(),
+                Json::Array(vec![Json::Object(sarif_location(source, error.span()))]),
+            ));
+            pairs.push(("relatedLocations".to_string(), Json::Array(related)));
+            pairs.push(("fixes".to_string(), Json::Array(fixes)));
+            Json::Object(pairs)
+        })
+        .collect();
+
+    Json::object([
+        (
+            "$schema",
+            "https://json.schemastore.org/sarif-2.1.0.json".into(),
+        ),
+        ("version", "2.1.0".into()),
+        (
+            "runs",
+            Json::Array(vec![Json::object([
+                (
+                    "tool",
+                    Json::object([(
+                        "driver",
+                        Json::object([
+                            ("name", tool_name.into()),
+                            ("version", tool_version.into()),
+                            (
+                                "rules",
+                                Json::Array(
+                                    rules
+                                        .into_iter()
+                                        .map(|rule| Json::object([("id", rule.into())]))
+                                        .collect(),
+                                ),
+                            ),
+                        ]),
+                    )]),
+                ),
+                ("columnKind", "unicodeCodePoints".into()),
+                ("results", Json::Array(results)),
+            ])]),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Applicability;
+
+    #[test]
+    fn test_json_display() {
+        let value = Json::object([
+            ("text", "a \"quoted\"\nline".into()),
+            ("count", 3.into()),
+            ("missing", Json::Null),
+            ("flags", Json::Array(vec![true.into(), false.into()])),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"text":"a \"quoted\"\nline","count":3,"missing":null,"flags":[true,false]}"#
+        );
+    }
+
+    #[test]
+    fn test_error_to_json() {
+        let source = Source::from_str("test.rs", "let x = 10\nlet y = x");
+        let error = Error::new(
+            "Unused variable `y`".to_string(),
+            &source,
+            Span::new(15, 16),
+        )
+        .with_severity(Severity::Warning)
+        .with_code("W0001".to_string())
+        .with_label(Span::new(4, 5), "`x` defined here".to_string())
+        .with_suggestion(
+            "Prefix it with an underscore".to_string(),
+            Span::new(15, 16),
+            "_y".to_string(),
+            Applicability::MachineApplicable,
+        );
+
+        let json = error.to_json();
+        assert_eq!(json.get("severity"), Some(&"warning".into()));
+        assert_eq!(json.get("code"), Some(&"W0001".into()));
+
+        let Some(Json::Array(labels)) = json.get("labels") else {
+            panic!("labels should be an array");
+        };
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0].get("line_start"), Some(&2.into()));
+        assert_eq!(labels[0].get("column_start"), Some(&5.into()));
+        assert_eq!(labels[0].get("column_end"), Some(&6.into()));
+        assert_eq!(labels[0].get("is_primary"), Some(&true.into()));
+        assert_eq!(labels[1].get("label"), Some(&"`x` defined here".into()));
+
+        let Some(Json::Array(helps)) = json.get("helps") else {
+            panic!("helps should be an array");
+        };
+        let suggestion = helps[0].get("suggestion").unwrap();
+        assert_eq!(suggestion.get("replacement"), Some(&"_y".into()));
+        assert_eq!(suggestion.get("byte_start"), Some(&15.into()));
+        assert_eq!(
+            suggestion.get("applicability"),
+            Some(&"MachineApplicable".into())
+        );
+    }
+
+    #[test]
+    fn test_sarif() {
+        let source = Source::from_str("src/my test.rs", "let x = 10");
+        let errors = vec![
+            Error::new("Syntax error".to_string(), &source, Span::empty(10))
+                .with_code("E0001".to_string()),
+            Error::new("Unused variable".to_string(), &source, Span::new(4, 5))
+                .with_severity(Severity::Note)
+                .with_label(Span::new(0, 3), "declared here".to_string()),
+        ];
+
+        let log = sarif(&errors, "runic", "0.0.1");
+        let output = log.to_string();
+
+        assert!(output.contains(r#""rules":[{"id":"E0001"}]"#));
+        assert!(output.contains(r#""ruleId":"E0001","level":"error""#));
+        assert!(output.contains(r#""level":"note""#));
+        assert!(output.contains(r#""columnKind":"unicodeCodePoints""#));
+        assert!(output.contains(r#""artifactLocation":{"uri":"src/my%20test.rs"}"#));
+        assert!(output.contains(r#""relatedLocations":[{"id":0,"physicalLocation":"#));
+        assert!(output.contains(r#""message":{"text":"declared here"}}]"#));
+        assert!(output.contains(
+            r#""region":{"startLine":1,"startColumn":11,"endLine":1,"endColumn":11,"byteOffset":10,"byteLength":0}"#
+        ));
+    }
+}
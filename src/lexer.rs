@@ -6,7 +6,7 @@
 
 // TODO: refactor
 
-use crate::{error::Error, source::Source, token::Token};
+use crate::{error::Error, intern::Interner, source::Source, span::Span, token::Token};
 
 /// Defines the interface for lexer rules.
 pub trait LexerRule<'a, T> {
@@ -17,7 +17,7 @@ pub trait LexerRule<'a, T> {
     ///
     /// If an error occurs, the lexer will stop processing and return the error.
     /// Otherwise, it will continue to the next rule.
-    fn get_token(&self, lexer: &mut Lexer<'a, T>) -> Result<Option<Token<T>>, Error>;
+    fn get_token(&self, lexer: &mut Lexer<'a, T>) -> Result<Option<Token<T>>, Error<'a>>;
     /// This method returns `true` if the rule generates a token,
     /// and `false` if it does not.
     ///
@@ -39,6 +39,8 @@ pub struct Lexer<'a, T> {
     pub position: usize,
     /// The current character being processed.
     pub current_char: Option<char>,
+    /// The interner that rules use to turn lexemes into symbols.
+    pub interner: Interner,
     /// The rules used to tokenize the source code.
     rules: Vec<Box<dyn LexerRule<'a, T>>>,
 }
@@ -50,6 +52,7 @@ impl<'a, T> Lexer<'a, T> {
             source,
             position: 0,
             current_char: None,
+            interner: Interner::new(),
             rules,
         };
 
@@ -62,25 +65,35 @@ impl<'a, T> Lexer<'a, T> {
         lexer
     }
 
+    /// Creates a new `Lexer`, changing the interner it fills.
+    ///
+    /// This is useful to share symbols between the files of a program.
+    pub fn with_interner(mut self, interner: Interner) -> Self {
+        self.interner = interner;
+        self
+    }
+
     /// Advances the lexer to the next character in the source code.
+    ///
+    /// At the end of the source code, the position is the length of the source code.
     pub fn advance(&mut self) {
-        if self.position < self.source.code.len() - 1 {
-            self.position += 1;
-            self.current_char = Some(self.source.code[self.position..].chars().next().unwrap());
-        } else {
-            self.current_char = None;
+        if let Some(c) = self.current_char {
+            self.position += c.len_utf8();
+            self.current_char = self.source.code[self.position..].chars().next();
         }
     }
 
     /// Jumps to a specific position in the source code.
+    ///
+    /// Positions past the end of the source code jump to its end. A position that is not on
+    /// a character boundary has no current character.
     pub fn jump_to(&mut self, position: usize) {
-        if position < self.source.code.len() {
-            self.position = position;
-            self.current_char = Some(self.source.code[self.position..].chars().next().unwrap());
-        } else {
-            self.position = self.source.code.len() + 1;
-            self.current_char = None;
-        }
+        self.position = position.min(self.source.code.len());
+        self.current_char = self
+            .source
+            .code
+            .get(self.position..)
+            .and_then(|rest| rest.chars().next());
     }
 
     /// Attempts to get the next token from the lexer using the defined rules.
@@ -88,7 +101,7 @@ impl<'a, T> Lexer<'a, T> {
     /// If a token is found, it returns `Ok(Some(token))`.
     /// If no token is found, it returns `Ok(None)`.
     /// If an error occurs, it returns `Err(error)`.
-    pub fn get_token(&mut self) -> Result<Option<Token<T>>, Error> {
+    pub fn get_token(&mut self) -> Result<Option<Token<T>>, Error<'a>> {
         // TODO: refactor this to avoid using unsafe?
 
         let self_ptr = self as *mut Self;
@@ -108,11 +121,38 @@ impl<'a, T> Lexer<'a, T> {
 
         Ok(None)
     }
+
+    /// Tokenizes the rest of the source code, returning every generated token.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error produced by a rule, or an error pointing at the first character
+    /// that no rule consumed.
+    pub fn tokenize(&mut self) -> Result<Vec<Token<T>>, Error<'a>> {
+        let mut tokens = Vec::new();
+
+        while let Some(c) = self.current_char {
+            let position = self.position;
+            match self.get_token()? {
+                Some(token) => tokens.push(token),
+                None if self.position == position => {
+                    return Err(Error::new(
+                        format!("unexpected character `{}`", c),
+                        self.source,
+                        Span::new(position, position + c.len_utf8()),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        Ok(tokens)
+    }
 }
 
 /// This module provides utility functions and common lexer rules.
 pub mod utils {
-    use crate::lexer::LexerRule;
+    use crate::{intern::Symbol, lexer::LexerRule, span::Span};
 
     mod macros {
         /// Creates a vector of lexer rules.
@@ -148,7 +188,7 @@ pub mod utils {
                     fn get_token(
                         &self,
                         lexer: &mut $crate::lexer::Lexer<'a, $token_type>,
-                    ) -> Result<Option<$crate::token::Token<$token_type>>, $crate::error::Error>
+                    ) -> Result<Option<$crate::token::Token<$token_type>>, $crate::error::Error<'a>>
                     {
                         let start_pos = lexer.position;
                         let mut matched = true;
@@ -196,7 +236,7 @@ pub mod utils {
                     fn get_token(
                         &self,
                         lexer: &mut $crate::lexer::Lexer<'a, $token_type>,
-                    ) -> Result<Option<$crate::token::Token<$token_type>>, $crate::error::Error>
+                    ) -> Result<Option<$crate::token::Token<$token_type>>, $crate::error::Error<'a>>
                     {
                         let start_pos = lexer.position;
                         let mut matched = true;
@@ -230,13 +270,84 @@ pub mod utils {
         pub use rules_vec;
     }
 
+    /// A lexer rule that matches identifiers, interning their text.
+    ///
+    /// Identifiers start with a letter or `_`, followed by letters, digits or `_`.
+    /// The kind of the token is built from the symbol of the identifier.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{
+    ///     intern::Symbol,
+    ///     lexer::{
+    ///         Lexer,
+    ///         utils::{IdentifierRule, SkipWhitespaceRule, rules_vec},
+    ///     },
+    ///     source::Source,
+    /// };
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Kind {
+    ///     Ident(Symbol),
+    /// }
+    ///
+    /// let source = Source::from_str("main.rn", "x y x");
+    /// let rules = rules_vec![SkipWhitespaceRule, IdentifierRule::new(Kind::Ident)];
+    /// let mut lexer = Lexer::new(&source, rules);
+    /// let tokens = lexer.tokenize().unwrap();
+    ///
+    /// assert_eq!(tokens[0].kind, tokens[2].kind);
+    /// assert_eq!(lexer.interner.len(), 2);
+    /// ```
+    pub struct IdentifierRule<F> {
+        /// Builds the kind of the token from the symbol of the identifier.
+        make_kind: F,
+    }
+
+    impl<F> IdentifierRule<F> {
+        /// Creates a new `IdentifierRule` that builds token kinds with `make_kind`.
+        pub fn new(make_kind: F) -> Self {
+            IdentifierRule { make_kind }
+        }
+    }
+
+    impl<'a, T, F: Fn(Symbol) -> T> LexerRule<'a, T> for IdentifierRule<F> {
+        fn get_token(
+            &self,
+            lexer: &mut super::Lexer<'a, T>,
+        ) -> Result<Option<crate::token::Token<T>>, crate::error::Error<'a>> {
+            let start = lexer.position;
+            match lexer.current_char {
+                Some(c) if c.is_alphabetic() || c == '_' => lexer.advance(),
+                _ => return Ok(None),
+            }
+
+            while let Some(c) = lexer.current_char {
+                if c.is_alphanumeric() || c == '_' {
+                    lexer.advance();
+                } else {
+                    break;
+                }
+            }
+
+            let symbol = lexer
+                .interner
+                .intern(&lexer.source.code[start..lexer.position]);
+            Ok(Some(crate::token::Token::new(
+                (self.make_kind)(symbol),
+                Span::new(start, lexer.position),
+            )))
+        }
+    }
+
     /// A lexer rule that skips whitespace characters.
     pub struct SkipWhitespaceRule;
     impl<'a, T> LexerRule<'a, T> for SkipWhitespaceRule {
         fn get_token(
             &self,
             lexer: &mut super::Lexer<'a, T>,
-        ) -> Result<Option<crate::token::Token<T>>, crate::error::Error> {
+        ) -> Result<Option<crate::token::Token<T>>, crate::error::Error<'a>> {
             while let Some(c) = lexer.current_char {
                 if c.is_whitespace() {
                     lexer.advance();
@@ -277,7 +388,7 @@ pub mod utils {
             let rules: Vec<Box<dyn LexerRule<'_, String> + 'static>> =
                 rules_vec![SkipWhitespaceRule];
             assert_eq!(rules.len(), 1);
-            assert!(rules[0].generates_token() == false);
+            assert!(!rules[0].generates_token());
         }
 
         #[test]
@@ -298,6 +409,19 @@ pub mod utils {
             assert_eq!(lexer.current_char, Some(' '));
         }
 
+        #[test]
+        fn test_identifier_rule() {
+            let source = Source::from_str("test_input.txt", "foo _bar1 foo");
+            let rules = rules_vec![SkipWhitespaceRule, IdentifierRule::new(|symbol| symbol)];
+            let mut lexer = Lexer::new(&source, rules);
+            let tokens = lexer.tokenize().unwrap();
+
+            assert_eq!(tokens.len(), 3);
+            assert_eq!(tokens[0].kind, tokens[2].kind);
+            assert_eq!(lexer.interner.resolve(tokens[1].kind), "_bar1");
+            assert_eq!(tokens[1].text(&source), "_bar1");
+        }
+
         #[test]
         fn test_match_word_macro() {
             match_word!("let", String, "let".to_string(), LetRule);
@@ -356,6 +480,15 @@ mod tests {
         lexer.jump_to(4);
         assert_eq!(lexer.position, 4);
         assert_eq!(lexer.current_char, Some('x'));
+
+        lexer.jump_to(100);
+        assert_eq!(lexer.position, 11);
+        assert_eq!(lexer.current_char, None);
+
+        let source = Source::from_str("test_input.txt", "é");
+        let mut lexer = Lexer::<u8>::new(&source, utils::rules_vec![]);
+        lexer.jump_to(1);
+        assert_eq!(lexer.current_char, None);
     }
 
     #[test]
@@ -367,7 +500,7 @@ mod tests {
             fn get_token(
                 &self,
                 lexer: &mut Lexer<'a, String>,
-            ) -> Result<Option<Token<String>>, Error> {
+            ) -> Result<Option<Token<String>>, Error<'a>> {
                 if lexer.current_char == Some('l') {
                     lexer.advance();
                     Ok(Some(Token::new("let".to_string(), Span::new(0, 3))))
@@ -389,4 +522,27 @@ mod tests {
         assert_eq!(token.span.start, 0);
         assert_eq!(token.span.end, 3);
     }
+
+    #[test]
+    fn test_lexer_tokenize() {
+        utils::match_string!("let", String, "let".to_string(), LetRule);
+        utils::match_string!("é", String, "é".to_string(), AccentRule);
+
+        let source = Source::from_str("test_input.txt", "let é let");
+        let rules = utils::rules_vec![utils::SkipWhitespaceRule, LetRule, AccentRule];
+        let mut lexer = Lexer::<String>::new(&source, rules);
+        let tokens = lexer.tokenize().unwrap();
+
+        let spans = tokens.iter().map(|token| token.span).collect::<Vec<Span>>();
+        assert_eq!(
+            spans,
+            vec![Span::new(0, 3), Span::new(4, 6), Span::new(7, 10)]
+        );
+
+        let source = Source::from_str("test_input.txt", "let ?");
+        let rules = utils::rules_vec![utils::SkipWhitespaceRule, LetRule];
+        let mut lexer = Lexer::<String>::new(&source, rules);
+        let error = lexer.tokenize().unwrap_err();
+        assert_eq!(error.message(), "unexpected character `?`");
+    }
 }
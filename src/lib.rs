@@ -1,5 +1,25 @@
+pub mod catalog;
+pub mod combinator;
+pub mod cst;
+pub mod diagnostics;
 pub mod error;
+pub mod expansion;
+pub mod fix;
+pub mod grammar;
+pub mod intern;
+pub mod json;
 pub mod lexer;
+pub mod loader;
+pub mod packrat;
+pub mod parser;
+pub mod pratt;
+pub mod recovery;
+pub mod render;
 pub mod source;
 pub mod span;
+#[cfg(test)]
+mod test_utils;
+pub mod testing;
+pub mod theme;
 pub mod token;
+pub mod token_tree;
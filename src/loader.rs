@@ -0,0 +1,205 @@
+//! This module defines the `FileLoader` trait, which abstracts over where source files are read from.
+//! It also provides loaders for the real file system, in-memory files, and overlays of the two.
+
+use std::{collections::HashMap, io, path::Path};
+
+/// Defines the interface for loading source files.
+pub trait FileLoader {
+    /// Reads the file at the given path into a string.
+    fn read(&self, path: &str) -> Result<String, io::Error>;
+
+    /// Returns `true` if a file exists at the given path.
+    fn exists(&self, path: &str) -> bool;
+
+    /// Resolves `path` relative to the directory of the file `from`.
+    ///
+    /// This is useful for resolving imports, where `from` is the importing file.
+    /// Absolute paths are returned unchanged.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::loader::{FileLoader, MemoryLoader};
+    ///
+    /// let loader = MemoryLoader::new();
+    /// assert_eq!(loader.resolve("src/main.rn", "util.rn"), "src/util.rn");
+    /// ```
+    fn resolve(&self, from: &str, path: &str) -> String {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return path.to_string_lossy().into_owned();
+        }
+
+        match Path::new(from).parent() {
+            Some(parent) => parent.join(path).to_string_lossy().into_owned(),
+            None => path.to_string_lossy().into_owned(),
+        }
+    }
+}
+
+/// A `FileLoader` that reads files from the real file system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiskLoader;
+
+impl FileLoader for DiskLoader {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        std::fs::read_to_string(path)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        Path::new(path).is_file()
+    }
+}
+
+/// A `FileLoader` that serves files from memory.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::loader::{FileLoader, MemoryLoader};
+///
+/// let mut loader = MemoryLoader::new();
+/// loader.insert("main.rn", "let x = 10;");
+/// assert_eq!(loader.read("main.rn").unwrap(), "let x = 10;");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MemoryLoader {
+    /// The files stored in memory, keyed by path.
+    files: HashMap<String, String>,
+}
+
+impl MemoryLoader {
+    /// Creates a new, empty `MemoryLoader`.
+    pub fn new() -> Self {
+        MemoryLoader {
+            files: HashMap::new(),
+        }
+    }
+
+    /// Adds a file to the loader, replacing any existing file at the same path.
+    pub fn insert(&mut self, path: impl Into<String>, code: impl Into<String>) {
+        self.files.insert(path.into(), code.into());
+    }
+
+    /// Removes a file from the loader, returning its contents if it existed.
+    pub fn remove(&mut self, path: &str) -> Option<String> {
+        self.files.remove(path)
+    }
+}
+
+impl FileLoader for MemoryLoader {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("file not found: {}", path))
+        })
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+}
+
+/// A `FileLoader` that serves in-memory files on top of another loader.
+///
+/// Files in the overlay take precedence over files in the base loader.
+/// This is useful for language servers, where unsaved editor buffers should shadow the files on disk.
+#[derive(Debug, Default, Clone)]
+pub struct OverlayLoader<L> {
+    /// The loader used for files that are not in the overlay.
+    pub base: L,
+    /// The files that shadow the base loader.
+    pub overlay: MemoryLoader,
+}
+
+impl<L: FileLoader> OverlayLoader<L> {
+    /// Creates a new `OverlayLoader` with an empty overlay on top of the given loader.
+    pub fn new(base: L) -> Self {
+        OverlayLoader {
+            base,
+            overlay: MemoryLoader::new(),
+        }
+    }
+
+    /// Shadows the file at the given path with the given code.
+    pub fn set(&mut self, path: impl Into<String>, code: impl Into<String>) {
+        self.overlay.insert(path, code);
+    }
+
+    /// Removes the shadowing file at the given path, exposing the base loader's file again.
+    pub fn clear(&mut self, path: &str) -> Option<String> {
+        self.overlay.remove(path)
+    }
+}
+
+impl<L: FileLoader> FileLoader for OverlayLoader<L> {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        if self.overlay.exists(path) {
+            self.overlay.read(path)
+        } else {
+            self.base.read(path)
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.overlay.exists(path) || self.base.exists(path)
+    }
+}
+
+impl<L: FileLoader + ?Sized> FileLoader for &L {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        (**self).read(path)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        (**self).exists(path)
+    }
+
+    fn resolve(&self, from: &str, path: &str) -> String {
+        (**self).resolve(from, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_loader() {
+        let mut loader = MemoryLoader::new();
+        loader.insert("main.rn", "let x = 10;");
+
+        assert!(loader.exists("main.rn"));
+        assert!(!loader.exists("other.rn"));
+        assert_eq!(loader.read("main.rn").unwrap(), "let x = 10;");
+        assert_eq!(
+            loader.read("other.rn").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        assert_eq!(loader.remove("main.rn"), Some("let x = 10;".to_string()));
+        assert!(!loader.exists("main.rn"));
+    }
+
+    #[test]
+    fn test_overlay_loader() {
+        let mut base = MemoryLoader::new();
+        base.insert("main.rn", "let x = 10;");
+        base.insert("util.rn", "let y = 20;");
+
+        let mut loader = OverlayLoader::new(base);
+        loader.set("main.rn", "let x = 11;");
+
+        assert_eq!(loader.read("main.rn").unwrap(), "let x = 11;");
+        assert_eq!(loader.read("util.rn").unwrap(), "let y = 20;");
+
+        loader.clear("main.rn");
+        assert_eq!(loader.read("main.rn").unwrap(), "let x = 10;");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let loader = MemoryLoader::new();
+        assert_eq!(loader.resolve("src/main.rn", "util.rn"), "src/util.rn");
+        assert_eq!(loader.resolve("main.rn", "util.rn"), "util.rn");
+    }
+}
@@ -0,0 +1,355 @@
+//! This module provides memoization for combinator parsers, also known as packrat parsing.
+//!
+//! A `Memo` remembers the result of each rule at each token position, so a rule is parsed at most
+//! once per position however much its callers backtrack, which makes PEG parsing linear-time.
+//! It also supports directly left-recursive rules such as `expr = expr "+" term`, by growing
+//! a seed: the rule first fails at the recursive call, then is parsed again with its previous
+//! result memoized, as long as each attempt consumes more tokens.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{
+    combinator::{ParseError, ParseResult, Parser, backtrack},
+    parser::{Checkpoint, Failure, TokenStream},
+    token::TokenKind,
+};
+
+/// Identifies a rule in a `Memo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RuleId(pub u32);
+
+/// A memoized result of a rule.
+#[derive(Debug, Clone)]
+struct Entry<O> {
+    /// The output and the position after it, or the error.
+    result: Result<(O, Checkpoint), ParseError>,
+    /// The furthest failure of a backtracked parser after the rule, replayed on a hit so that
+    /// error messages do not depend on whether the result was memoized.
+    failure: Option<Failure>,
+}
+
+/// Stores the results of rules by token position.
+///
+/// A `Memo` is tied to a single `TokenStream`: positions of another stream would give wrong
+/// results, so use a new `Memo`, or `clear` it, for each stream.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     combinator::{ParseResult, Parser, choice, map, seq, token},
+///     packrat::{Memo, RuleId},
+///     parser::TokenStream,
+///     source::Source,
+///     span::Span,
+///     token::Token,
+/// };
+///
+/// const SUM: RuleId = RuleId(0);
+///
+/// // sum = sum "+" "x" / "x"
+/// fn sum<'a>(memo: &Memo<usize>, stream: &mut TokenStream<'a, &'a str>) -> ParseResult<usize> {
+///     memo.parse_left_recursive(SUM, stream, |stream: &mut TokenStream<'a, &'a str>| {
+///         let recursive = |stream: &mut TokenStream<'a, &'a str>| sum(memo, stream);
+///         choice((
+///             map(seq((recursive, token("+"), token("x"))), |(n, _, _)| n + 1),
+///             map(token("x"), |_| 1),
+///         ))
+///         .parse(stream)
+///     })
+/// }
+///
+/// let source = Source::from_str("main.rn", "x+x+x");
+/// let tokens = ["x", "+", "x", "+", "x"]
+///     .iter()
+///     .enumerate()
+///     .map(|(i, kind)| Token::new(*kind, Span::new(i, i + 1)))
+///     .collect();
+/// let mut stream = TokenStream::new(&source, tokens);
+///
+/// assert_eq!(sum(&Memo::new(), &mut stream), Ok(3));
+/// assert!(stream.is_at_end());
+/// ```
+#[derive(Debug)]
+pub struct Memo<O> {
+    /// The results of the rules, by rule and start position.
+    entries: RefCell<HashMap<(RuleId, Checkpoint), Entry<O>>>,
+}
+
+impl<O: Clone> Memo<O> {
+    /// Creates a new, empty `Memo`.
+    pub fn new() -> Self {
+        Memo {
+            entries: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Parses the rule with the parser, or returns its memoized result at this position.
+    ///
+    /// The parser must not be left-recursive; use `parse_left_recursive` for such rules.
+    pub fn parse<'a, T>(
+        &self,
+        rule: RuleId,
+        stream: &mut TokenStream<'a, T>,
+        parser: impl Parser<'a, T, O>,
+    ) -> ParseResult<O> {
+        let start = stream.mark();
+        if let Some(result) = self.recall(rule, start, stream) {
+            return result;
+        }
+
+        let result = parser.parse(stream);
+        self.store(rule, start, &result, stream);
+        result
+    }
+
+    /// Parses a directly left-recursive rule with the parser, or returns its memoized result at
+    /// this position.
+    ///
+    /// The recursive call at the start of the rule first fails, so that the non-recursive
+    /// alternatives match a seed. The rule is then parsed again, with the recursive call returning
+    /// the previous result, for as long as each attempt consumes more tokens than the last.
+    pub fn parse_left_recursive<'a, T: TokenKind>(
+        &self,
+        rule: RuleId,
+        stream: &mut TokenStream<'a, T>,
+        parser: impl Parser<'a, T, O>,
+    ) -> ParseResult<O> {
+        let start = stream.mark();
+        if let Some(result) = self.recall(rule, start, stream) {
+            return result;
+        }
+
+        let seed = Err(ParseError::expected(stream, Vec::new()));
+        self.store(rule, start, &seed, stream);
+
+        let mut grown: Option<Checkpoint> = None;
+        loop {
+            stream.reset(start);
+            match (parser.parse(stream), grown) {
+                (Ok(output), None) => {
+                    grown = Some(stream.mark());
+                    self.store(rule, start, &Ok(output), stream);
+                }
+                (Ok(output), Some(end)) if stream.mark() > end => {
+                    grown = Some(stream.mark());
+                    self.store(rule, start, &Ok(output), stream);
+                }
+                (Ok(_), Some(_)) => break,
+                (Err(error), Some(end)) if !error.committed => {
+                    backtrack(stream, end, error);
+                    break;
+                }
+                (Err(error), _) => {
+                    stream.reset(start);
+                    self.store(rule, start, &Err(error.clone()), stream);
+                    return Err(error);
+                }
+            }
+        }
+
+        stream.reset(start);
+        self.recall(rule, start, stream)
+            .expect("the grown result is memoized")
+    }
+
+    /// Forgets every memoized result, to reuse the `Memo` for another stream.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Returns the memoized result of the rule at `start`, moving the stream after it on success
+    /// and replaying the failures recorded while parsing it.
+    fn recall<T>(
+        &self,
+        rule: RuleId,
+        start: Checkpoint,
+        stream: &mut TokenStream<'_, T>,
+    ) -> Option<ParseResult<O>> {
+        let entry = self.entries.borrow().get(&(rule, start)).cloned()?;
+        if let Some(failure) = &entry.failure {
+            stream.record_failure(failure.clone());
+        }
+        Some(entry.result.map(|(output, end)| {
+            stream.reset(end);
+            output
+        }))
+    }
+
+    /// Memoizes the result of the rule at `start`, which ends at the position of the stream on
+    /// success, along with the furthest failure of the stream.
+    fn store<T>(
+        &self,
+        rule: RuleId,
+        start: Checkpoint,
+        result: &ParseResult<O>,
+        stream: &TokenStream<'_, T>,
+    ) {
+        let entry = Entry {
+            result: match result {
+                Ok(output) => Ok((output.clone(), stream.mark())),
+                Err(error) => Err(error.clone()),
+            },
+            failure: stream.furthest_failure().cloned(),
+        };
+        self.entries.borrow_mut().insert((rule, start), entry);
+    }
+}
+
+impl<O: Clone> Default for Memo<O> {
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{
+        combinator::{choice, many, map, run, seq, token},
+        source::Source,
+        span::Span,
+        test_utils::{parse_words, token_stream},
+    };
+
+    const EXPR: RuleId = RuleId(0);
+    const ATOM: RuleId = RuleId(1);
+
+    /// `expr = expr "-" atom / atom`, printing the tree with parentheses.
+    fn expr<'a>(memo: &Memo<String>, stream: &mut TokenStream<'a, &'a str>) -> ParseResult<String> {
+        memo.parse_left_recursive(EXPR, stream, |stream: &mut TokenStream<'a, &'a str>| {
+            let recursive = |stream: &mut TokenStream<'a, &'a str>| expr(memo, stream);
+            let atom = |stream: &mut TokenStream<'a, &'a str>| atom(memo, stream);
+            choice((
+                map(seq((recursive, token("-"), atom)), |(left, _, right)| {
+                    format!("({}-{})", left, right)
+                }),
+                atom,
+            ))
+            .parse(stream)
+        })
+    }
+
+    fn atom<'a>(memo: &Memo<String>, stream: &mut TokenStream<'a, &'a str>) -> ParseResult<String> {
+        memo.parse(
+            ATOM,
+            stream,
+            map(choice((token("a"), token("b"), token("c"))), |token| {
+                token.kind.to_string()
+            }),
+        )
+    }
+
+    fn parse(code: &str) -> Result<String, (String, Span)> {
+        let memo = Memo::new();
+        parse_words(code, |stream| {
+            run(&|stream: &mut _| expr(&memo, stream), stream)
+        })
+    }
+
+    #[test]
+    fn test_left_recursion() {
+        assert_eq!(parse("a - b - c").unwrap(), "((a-b)-c)");
+        assert_eq!(parse("b").unwrap(), "b");
+        assert_eq!(
+            parse("a - 1").unwrap_err(),
+            (
+                "expected one of `a`, `b` or `c`, found `1`".to_string(),
+                Span::new(4, 5)
+            )
+        );
+
+        let source = Source::from_str("test.rn", "1");
+        let mut stream = token_stream(&source);
+        let start = stream.mark();
+        let error = expr(&Memo::new(), &mut stream).unwrap_err();
+        assert_eq!(
+            error.into_error(&source).message(),
+            "expected one of `a`, `b` or `c`, found `1`"
+        );
+        assert_eq!(stream.mark(), start);
+    }
+
+    #[test]
+    fn test_left_recursion_with_empty_seed() {
+        const LIST: RuleId = RuleId(2);
+
+        // list = list "x" / ε
+        fn list<'a>(
+            memo: &Memo<usize>,
+            stream: &mut TokenStream<'a, &'a str>,
+        ) -> ParseResult<usize> {
+            memo.parse_left_recursive(LIST, stream, |stream: &mut TokenStream<'a, &'a str>| {
+                let recursive = |stream: &mut TokenStream<'a, &'a str>| list(memo, stream);
+                choice((
+                    map(seq((recursive, token("x"))), |(n, _)| n + 1),
+                    |_: &mut TokenStream<'a, &'a str>| Ok(0),
+                ))
+                .parse(stream)
+            })
+        }
+
+        for (code, count, end) in [("x x", 2, 3), ("y", 0, 0), ("", 0, 0)] {
+            let source = Source::from_str("test.rn", code);
+            let mut stream = token_stream(&source);
+            assert_eq!(list(&Memo::new(), &mut stream), Ok(count));
+            assert_eq!(stream.current_span().start, end);
+        }
+    }
+
+    #[test]
+    fn test_memoization_avoids_reparsing() {
+        let source = Source::from_str("test.rn", "a");
+        let mut stream = token_stream(&source);
+        let start = stream.mark();
+
+        let calls = Cell::new(0);
+        let memo = Memo::new();
+        let term = |stream: &mut TokenStream<'_, &str>| {
+            memo.parse(ATOM, stream, |stream: &mut TokenStream<'_, &str>| {
+                calls.set(calls.get() + 1);
+                token("a").parse(stream).map(|_| ())
+            })
+        };
+        let parser = choice((
+            map(seq((term, token("+"))), |_| "plus"),
+            map(seq((term, token("-"))), |_| "minus"),
+            map(term, |_| "term"),
+        ));
+
+        assert_eq!(parser.parse(&mut stream), Ok("term"));
+        assert_eq!(calls.get(), 1);
+
+        memo.clear();
+        stream.reset(start);
+        assert_eq!(parser.parse(&mut stream), Ok("term"));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_memoization_replays_failures() {
+        let source = Source::from_str("test.rn", "a a *");
+        let memo = Memo::new();
+        let term = |stream: &mut TokenStream<'_, &str>| {
+            memo.parse(ATOM, stream, map(many(token("a")), |tokens| tokens.len()))
+        };
+        let parser = choice((
+            map(seq((term, token("+"))), |(n, _)| n),
+            map(seq((term, token("-"))), |(n, _)| n),
+        ));
+
+        let mut stream = token_stream(&source);
+        let start = stream.mark();
+        for _ in 0..2 {
+            stream.reset(start);
+            stream.clear_failures();
+            let error = parser.parse(&mut stream).unwrap_err();
+            assert_eq!(
+                error.into_error(&source).message(),
+                "expected one of `a`, `+` or `-`, found `*`"
+            );
+        }
+    }
+}
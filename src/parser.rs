@@ -0,0 +1,313 @@
+//! This module defines the `TokenStream` struct, a cursor over the tokens produced by a lexer.
+//!
+//! Parsers consume tokens through the stream, and can save and restore its position to backtrack.
+
+use crate::{
+    error::Error,
+    source::Source,
+    span::Span,
+    token::{Token, TokenKind, describe_expected},
+};
+
+/// A saved position of a `TokenStream`, used to backtrack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checkpoint(usize);
+
+/// What a backtracked parser expected where it failed, and what it found there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Failure {
+    /// The span of the offending token, or the end-of-file span.
+    pub(crate) span: Span,
+    /// The descriptions of what was expected instead.
+    pub(crate) expected: Vec<String>,
+    /// The description of what was found.
+    pub(crate) found: String,
+}
+
+/// A cursor over the tokens of a source.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{parser::TokenStream, source::Source, span::Span, token::Token};
+///
+/// let source = Source::from_str("main.rn", "let x");
+/// let tokens = vec![
+///     Token::new("let", Span::new(0, 3)),
+///     Token::new("ident", Span::new(4, 5)),
+/// ];
+/// let mut stream = TokenStream::new(&source, tokens);
+///
+/// assert!(stream.eat(&"let").is_some());
+/// assert!(stream.check(&"ident"));
+///
+/// let error = stream.expect(&"=", "expected `=`").unwrap_err();
+/// assert_eq!(error.message(), "expected `=`");
+/// ```
+#[derive(Debug)]
+pub struct TokenStream<'a, T> {
+    /// The source code the tokens were produced from.
+    source: &'a Source<'a>,
+    /// The tokens of the stream.
+    tokens: Vec<Token<T>>,
+    /// The index of the next token.
+    position: usize,
+    /// The furthest failure of a backtracked parser, merged into later errors.
+    furthest: Option<Failure>,
+}
+
+impl<'a, T> TokenStream<'a, T> {
+    /// Creates a new `TokenStream` over the given tokens of the source.
+    pub fn new(source: &'a Source<'a>, tokens: Vec<Token<T>>) -> Self {
+        TokenStream {
+            source,
+            tokens,
+            position: 0,
+            furthest: None,
+        }
+    }
+
+    /// Returns the source code the tokens were produced from.
+    pub fn source(&self) -> &'a Source<'a> {
+        self.source
+    }
+
+    /// Returns the next token without consuming it.
+    pub fn peek(&self) -> Option<&Token<T>> {
+        self.peek_nth(0)
+    }
+
+    /// Returns the token `k` positions after the next one without consuming anything.
+    ///
+    /// `peek_nth(0)` is the same as `peek()`.
+    pub fn peek_nth(&self, k: usize) -> Option<&Token<T>> {
+        self.tokens.get(self.position + k)
+    }
+
+    /// Consumes and returns the next token.
+    ///
+    /// Unlike `Iterator::next`, this borrows the token, so that the stream can be rewound.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&Token<T>> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    /// Returns the previously consumed token, if any.
+    pub fn previous(&self) -> Option<&Token<T>> {
+        self.position
+            .checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+    }
+
+    /// Returns `true` if every token has been consumed.
+    pub fn is_at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    /// Returns the zero-width span at the end of the source code.
+    pub fn eof_span(&self) -> Span {
+        Span::empty(self.source.code.len())
+    }
+
+    /// Returns the span of the next token, or the end-of-file span if there are none left.
+    pub fn current_span(&self) -> Span {
+        self.peek()
+            .map_or_else(|| self.eof_span(), |token| token.span)
+    }
+
+    /// Saves the current position, to return to it later with `reset`.
+    pub fn mark(&self) -> Checkpoint {
+        Checkpoint(self.position)
+    }
+
+    /// Returns to the position saved in the checkpoint.
+    pub fn reset(&mut self, checkpoint: Checkpoint) {
+        self.position = checkpoint.0;
+    }
+
+    /// Records the failure of a backtracked parser, keeping the one that got the furthest
+    /// and merging the expected sets of failures at the same position.
+    pub(crate) fn record_failure(&mut self, failure: Failure) {
+        match &mut self.furthest {
+            Some(furthest) if furthest.span.start == failure.span.start => {
+                for expected in failure.expected {
+                    if !furthest.expected.contains(&expected) {
+                        furthest.expected.push(expected);
+                    }
+                }
+            }
+            Some(furthest) if furthest.span.start > failure.span.start => {}
+            _ => self.furthest = Some(failure),
+        }
+    }
+
+    /// Returns the furthest failure of a backtracked parser, if any.
+    pub(crate) fn furthest_failure(&self) -> Option<&Failure> {
+        self.furthest.as_ref()
+    }
+
+    /// Forgets the failures of backtracked parsers, to parse the stream again from the start.
+    ///
+    /// Unlike the position, failures are kept by `reset`, so that they can be reported after
+    /// backtracking.
+    pub fn clear_failures(&mut self) {
+        self.furthest = None;
+    }
+
+    /// Returns what backtracked parsers expected at the current position.
+    pub(crate) fn hint(&self) -> Vec<String> {
+        match &self.furthest {
+            Some(furthest) if furthest.span.start == self.current_span().start => {
+                furthest.expected.clone()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Creates an error spanning the next token, or the end of the source code if there are none left.
+    pub fn error(&self, message: &str) -> Error<'a> {
+        Error::new(message.to_string(), self.source, self.current_span())
+    }
+}
+
+impl<'a, T: PartialEq> TokenStream<'a, T> {
+    /// Returns `true` if the next token is of the given kind.
+    pub fn check(&self, kind: &T) -> bool {
+        self.peek().is_some_and(|token| token.kind == *kind)
+    }
+
+    /// Consumes and returns the next token if it is of the given kind.
+    pub fn eat(&mut self, kind: &T) -> Option<&Token<T>> {
+        if self.check(kind) { self.next() } else { None }
+    }
+
+    /// Consumes and returns the next token if it is of the given kind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the given message, spanning the offending token,
+    /// or the end of the source code if there are no tokens left.
+    pub fn expect(&mut self, kind: &T, message: &str) -> Result<&Token<T>, Error<'a>> {
+        if self.check(kind) {
+            Ok(self.next().expect("checked token exists"))
+        } else {
+            Err(self.error(message))
+        }
+    }
+}
+
+impl<'a, T: TokenKind> TokenStream<'a, T> {
+    /// Returns `true` if the next token is of the same kind as `kind`, ignoring payloads.
+    pub fn check_kind(&self, kind: &T) -> bool {
+        self.peek().is_some_and(|token| token.kind.same_kind(kind))
+    }
+
+    /// Consumes and returns the next token if it is of the same kind as `kind`, ignoring payloads.
+    pub fn eat_kind(&mut self, kind: &T) -> Option<&Token<T>> {
+        if self.check_kind(kind) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
+    /// Consumes and returns the next token if it is of the same kind as one of `kinds`, ignoring payloads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error such as "expected one of `,`, `;` or `}`, found `)`", spanning the offending
+    /// token, or the end of the source code if there are no tokens left.
+    pub fn expect_one_of(&mut self, kinds: &[T]) -> Result<&Token<T>, Error<'a>> {
+        if kinds.iter().any(|kind| self.check_kind(kind)) {
+            return Ok(self.next().expect("checked token exists"));
+        }
+
+        let found = self
+            .peek()
+            .map_or_else(|| "end of file".to_string(), |token| token.kind.describe());
+        Err(self.error(&format!(
+            "expected {}, found {}",
+            describe_expected(kinds),
+            found
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::HasSpan;
+
+    fn stream<'a>(source: &'a Source<'a>) -> TokenStream<'a, &'static str> {
+        TokenStream::new(
+            source,
+            vec![
+                Token::new("let", Span::new(0, 3)),
+                Token::new("ident", Span::new(4, 5)),
+                Token::new("=", Span::new(6, 7)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_token_stream_peek_and_next() {
+        let source = Source::from_str("test.rn", "let x =");
+        let mut stream = stream(&source);
+
+        assert_eq!(stream.peek().unwrap().kind, "let");
+        assert_eq!(stream.peek_nth(2).unwrap().kind, "=");
+        assert!(stream.peek_nth(3).is_none());
+
+        assert_eq!(stream.next().unwrap().kind, "let");
+        assert_eq!(stream.previous().unwrap().kind, "let");
+        assert!(stream.eat(&"=").is_none());
+        assert!(stream.eat(&"ident").is_some());
+        assert!(stream.expect(&"=", "expected `=`").is_ok());
+        assert!(stream.is_at_end());
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_token_stream_expect_errors() {
+        let source = Source::from_str("test.rn", "let x =");
+        let mut stream = stream(&source);
+
+        let error = stream.expect(&"ident", "expected identifier").unwrap_err();
+        assert_eq!(error.span(), Span::new(0, 3));
+
+        stream.reset(Checkpoint(3));
+        let error = stream.expect(&"ident", "expected identifier").unwrap_err();
+        assert_eq!(error.span(), Span::empty(7));
+    }
+
+    #[test]
+    fn test_token_stream_mark_and_reset() {
+        let source = Source::from_str("test.rn", "let x =");
+        let mut stream = stream(&source);
+
+        stream.next();
+        let checkpoint = stream.mark();
+        stream.next();
+        stream.next();
+        assert!(stream.is_at_end());
+
+        stream.reset(checkpoint);
+        assert_eq!(stream.peek().unwrap().kind, "ident");
+    }
+
+    #[test]
+    fn test_token_stream_expect_one_of() {
+        let source = Source::from_str("test.rn", "let x =");
+        let mut stream = stream(&source);
+
+        assert!(stream.eat_kind(&"let").is_some());
+        let error = stream.expect_one_of(&["=", ";"]).unwrap_err();
+        assert_eq!(error.message(), "expected `=` or `;`, found `ident`");
+
+        stream.reset(Checkpoint(3));
+        let error = stream.expect_one_of(&[";"]).unwrap_err();
+        assert_eq!(error.message(), "expected `;`, found end of file");
+    }
+}
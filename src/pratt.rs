@@ -0,0 +1,511 @@
+//! This module defines the `PrattParser` struct, a reusable expression parser based on Pratt parsing
+//! (precedence climbing).
+//!
+//! Operators are registered in tables with a precedence level and, for infix operators, an
+//! associativity. Higher precedence levels bind tighter. Callbacks build the nodes of the AST.
+
+use crate::{
+    error::Error,
+    parser::TokenStream,
+    span::Span,
+    token::{Token, TokenKind},
+};
+
+/// Describes how infix operators of the same precedence group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Associativity {
+    /// `a - b - c` is parsed as `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is parsed as `a ^ (b ^ c)`.
+    Right,
+    /// `a < b < c` is an error.
+    None,
+}
+
+/// Parses atoms, such as literals and identifiers.
+type AtomFn<'a, T, N> = dyn Fn(&mut TokenStream<'a, T>) -> Result<Option<N>, Error<'a>> + 'a;
+
+/// An operator that starts an expression.
+enum PrefixOperator<'a, T, N> {
+    /// A unary operator, such as `-a`.
+    Unary {
+        kind: T,
+        precedence: u16,
+        build: Box<dyn Fn(Token<T>, N) -> N + 'a>,
+    },
+    /// A parenthesized expression, such as `(a)`.
+    Group {
+        open: T,
+        close: T,
+        build: Box<dyn Fn(N, Span) -> N + 'a>,
+    },
+}
+
+/// An operator that follows an expression.
+enum PostfixOperator<'a, T, N> {
+    /// A binary operator, such as `a + b`.
+    Infix {
+        kind: T,
+        precedence: u16,
+        associativity: Associativity,
+        build: Box<dyn Fn(N, Token<T>, N) -> N + 'a>,
+    },
+    /// A unary operator, such as `a?`.
+    Postfix {
+        kind: T,
+        precedence: u16,
+        build: Box<dyn Fn(N, Token<T>) -> N + 'a>,
+    },
+    /// A conditional operator, such as `a ? b : c`.
+    Ternary {
+        question: T,
+        colon: T,
+        precedence: u16,
+        build: Box<dyn Fn(N, N, N) -> N + 'a>,
+    },
+    /// A call, such as `f(a, b)`.
+    Call {
+        open: T,
+        separator: T,
+        close: T,
+        precedence: u16,
+        build: Box<dyn Fn(N, Vec<N>, Span) -> N + 'a>,
+    },
+    /// An index, such as `a[b]`.
+    Index {
+        open: T,
+        close: T,
+        precedence: u16,
+        build: Box<dyn Fn(N, N, Span) -> N + 'a>,
+    },
+}
+
+impl<T, N> PostfixOperator<'_, T, N> {
+    /// Returns the token kind that starts the operator.
+    fn kind(&self) -> &T {
+        match self {
+            PostfixOperator::Infix { kind, .. } | PostfixOperator::Postfix { kind, .. } => kind,
+            PostfixOperator::Ternary { question, .. } => question,
+            PostfixOperator::Call { open, .. } | PostfixOperator::Index { open, .. } => open,
+        }
+    }
+
+    /// Returns the left binding power of the operator.
+    fn left_binding_power(&self) -> u32 {
+        match self {
+            PostfixOperator::Infix { precedence, .. }
+            | PostfixOperator::Postfix { precedence, .. }
+            | PostfixOperator::Call { precedence, .. }
+            | PostfixOperator::Index { precedence, .. } => binding_power(*precedence),
+            PostfixOperator::Ternary { precedence, .. } => binding_power(*precedence) + 1,
+        }
+    }
+}
+
+/// Converts a precedence level to a binding power, leaving room for associativity.
+fn binding_power(precedence: u16) -> u32 {
+    (u32::from(precedence) + 1) * 2
+}
+
+/// Parses expressions from a `TokenStream` using operator tables.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     parser::TokenStream,
+///     pratt::{Associativity, PrattParser},
+///     source::Source,
+///     span::Span,
+///     token::Token,
+/// };
+///
+/// let source = Source::from_str("main.rn", "1 + 2 * 3");
+/// let tokens = vec![
+///     Token::new("num", Span::new(0, 1)),
+///     Token::new("+", Span::new(2, 3)),
+///     Token::new("num", Span::new(4, 5)),
+///     Token::new("*", Span::new(6, 7)),
+///     Token::new("num", Span::new(8, 9)),
+/// ];
+///
+/// let parser = PrattParser::new(|stream: &mut TokenStream<'_, &str>| {
+///     let source = stream.source();
+///     Ok(stream.eat(&"num").map(|token| token.text(source).to_string()))
+/// })
+/// .infix("+", 1, Associativity::Left, |a, _, b| format!("({} + {})", a, b))
+/// .infix("*", 2, Associativity::Left, |a, _, b| format!("({} * {})", a, b));
+///
+/// let mut stream = TokenStream::new(&source, tokens);
+/// assert_eq!(parser.parse(&mut stream).unwrap(), "(1 + (2 * 3))");
+/// ```
+pub struct PrattParser<'a, T, N> {
+    /// Parses atoms.
+    atom: Box<AtomFn<'a, T, N>>,
+    /// The operators that start an expression.
+    prefix: Vec<PrefixOperator<'a, T, N>>,
+    /// The operators that follow an expression.
+    postfix: Vec<PostfixOperator<'a, T, N>>,
+}
+
+impl<'a, T: TokenKind + Clone, N> PrattParser<'a, T, N> {
+    /// Creates a new `PrattParser` that parses atoms with the given callback.
+    ///
+    /// The callback returns `Ok(None)` without consuming anything if the next token does not start an atom.
+    pub fn new(
+        atom: impl Fn(&mut TokenStream<'a, T>) -> Result<Option<N>, Error<'a>> + 'a,
+    ) -> Self {
+        PrattParser {
+            atom: Box::new(atom),
+            prefix: Vec::new(),
+            postfix: Vec::new(),
+        }
+    }
+
+    /// Adds a prefix operator, such as `-a`.
+    pub fn prefix(
+        mut self,
+        kind: T,
+        precedence: u16,
+        build: impl Fn(Token<T>, N) -> N + 'a,
+    ) -> Self {
+        self.prefix.push(PrefixOperator::Unary {
+            kind,
+            precedence,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds a parenthesized expression, such as `(a)`.
+    ///
+    /// The callback receives the inner expression and the span from `open` to `close`.
+    pub fn group(mut self, open: T, close: T, build: impl Fn(N, Span) -> N + 'a) -> Self {
+        self.prefix.push(PrefixOperator::Group {
+            open,
+            close,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds an infix operator, such as `a + b`.
+    pub fn infix(
+        mut self,
+        kind: T,
+        precedence: u16,
+        associativity: Associativity,
+        build: impl Fn(N, Token<T>, N) -> N + 'a,
+    ) -> Self {
+        self.postfix.push(PostfixOperator::Infix {
+            kind,
+            precedence,
+            associativity,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds a postfix operator, such as `a?`.
+    pub fn postfix(
+        mut self,
+        kind: T,
+        precedence: u16,
+        build: impl Fn(N, Token<T>) -> N + 'a,
+    ) -> Self {
+        self.postfix.push(PostfixOperator::Postfix {
+            kind,
+            precedence,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds a right-associative conditional operator, such as `a ? b : c`.
+    pub fn ternary(
+        mut self,
+        question: T,
+        colon: T,
+        precedence: u16,
+        build: impl Fn(N, N, N) -> N + 'a,
+    ) -> Self {
+        self.postfix.push(PostfixOperator::Ternary {
+            question,
+            colon,
+            precedence,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds a call, such as `f(a, b)`, with an optional trailing separator.
+    ///
+    /// The callback receives the callee, the arguments and the span from `open` to `close`.
+    pub fn call(
+        mut self,
+        open: T,
+        separator: T,
+        close: T,
+        precedence: u16,
+        build: impl Fn(N, Vec<N>, Span) -> N + 'a,
+    ) -> Self {
+        self.postfix.push(PostfixOperator::Call {
+            open,
+            separator,
+            close,
+            precedence,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds an index, such as `a[b]`.
+    ///
+    /// The callback receives the indexed expression, the index and the span from `open` to `close`.
+    pub fn index(
+        mut self,
+        open: T,
+        close: T,
+        precedence: u16,
+        build: impl Fn(N, N, Span) -> N + 'a,
+    ) -> Self {
+        self.postfix.push(PostfixOperator::Index {
+            open,
+            close,
+            precedence,
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Parses an expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error pointing at the offending token (or the end of the source code) if no
+    /// expression starts there, if a closing token is missing, or if non-associative operators are chained.
+    pub fn parse(&self, stream: &mut TokenStream<'a, T>) -> Result<N, Error<'a>> {
+        self.parse_binding_power(stream, 0)
+    }
+
+    /// Parses an expression whose operators bind at least as tightly as the given precedence level.
+    pub fn parse_with_precedence(
+        &self,
+        stream: &mut TokenStream<'a, T>,
+        precedence: u16,
+    ) -> Result<N, Error<'a>> {
+        self.parse_binding_power(stream, binding_power(precedence))
+    }
+
+    /// Parses an expression whose operators have at least the given left binding power.
+    fn parse_binding_power(
+        &self,
+        stream: &mut TokenStream<'a, T>,
+        min_binding_power: u32,
+    ) -> Result<N, Error<'a>> {
+        let mut lhs = self.parse_prefix(stream)?;
+        let mut chained: Option<u16> = None;
+
+        while let Some(token) = stream.peek() {
+            let Some(operator) = self
+                .postfix
+                .iter()
+                .find(|operator| token.kind.same_kind(operator.kind()))
+            else {
+                break;
+            };
+            if operator.left_binding_power() < min_binding_power {
+                break;
+            }
+
+            let token = stream.next().expect("peeked token exists").clone();
+            lhs = match operator {
+                PostfixOperator::Infix {
+                    precedence,
+                    associativity,
+                    build,
+                    ..
+                } => {
+                    if *associativity == Associativity::None {
+                        if chained == Some(*precedence) {
+                            return Err(Error::new(
+                                format!("{} cannot be chained", token.kind.describe()),
+                                stream.source(),
+                                token.span,
+                            )
+                            .with_help("use parentheses to group the operands".to_string()));
+                        }
+                        chained = Some(*precedence);
+                    }
+
+                    let right_binding_power = match associativity {
+                        Associativity::Right => binding_power(*precedence),
+                        Associativity::Left | Associativity::None => binding_power(*precedence) + 1,
+                    };
+                    let rhs = self.parse_binding_power(stream, right_binding_power)?;
+                    build(lhs, token, rhs)
+                }
+                PostfixOperator::Postfix { build, .. } => build(lhs, token),
+                PostfixOperator::Ternary {
+                    colon,
+                    precedence,
+                    build,
+                    ..
+                } => {
+                    let then = self.parse_binding_power(stream, 0)?;
+                    stream.expect_one_of(std::slice::from_ref(colon))?;
+                    let otherwise = self.parse_binding_power(stream, binding_power(*precedence))?;
+                    build(lhs, then, otherwise)
+                }
+                PostfixOperator::Call {
+                    separator,
+                    close,
+                    build,
+                    ..
+                } => {
+                    let mut arguments = Vec::new();
+                    while !stream.check_kind(close) {
+                        arguments.push(self.parse_binding_power(stream, 0)?);
+                        if stream.eat_kind(separator).is_none() {
+                            break;
+                        }
+                    }
+                    let end = stream.expect_one_of(std::slice::from_ref(close))?.span;
+                    build(lhs, arguments, token.span.to(end))
+                }
+                PostfixOperator::Index { close, build, .. } => {
+                    let index = self.parse_binding_power(stream, 0)?;
+                    let end = stream.expect_one_of(std::slice::from_ref(close))?.span;
+                    build(lhs, index, token.span.to(end))
+                }
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parses a prefix operator applied to an expression, a group or an atom.
+    fn parse_prefix(&self, stream: &mut TokenStream<'a, T>) -> Result<N, Error<'a>> {
+        let operator = stream.peek().and_then(|token| {
+            self.prefix.iter().find(|operator| match operator {
+                PrefixOperator::Unary { kind, .. } => token.kind.same_kind(kind),
+                PrefixOperator::Group { open, .. } => token.kind.same_kind(open),
+            })
+        });
+
+        match operator {
+            Some(PrefixOperator::Unary {
+                precedence, build, ..
+            }) => {
+                let token = stream.next().expect("peeked token exists").clone();
+                let operand = self.parse_binding_power(stream, binding_power(*precedence))?;
+                Ok(build(token, operand))
+            }
+            Some(PrefixOperator::Group { close, build, .. }) => {
+                let start = stream.next().expect("peeked token exists").span;
+                let inner = self.parse_binding_power(stream, 0)?;
+                let end = stream.expect_one_of(std::slice::from_ref(close))?.span;
+                Ok(build(inner, start.to(end)))
+            }
+            None => match (self.atom)(stream)? {
+                Some(atom) => Ok(atom),
+                None => {
+                    let found = stream
+                        .peek()
+                        .map_or_else(|| "end of file".to_string(), |token| token.kind.describe());
+                    Err(stream.error(&format!("expected expression, found {}", found)))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::parse_words;
+
+    fn parser<'a>() -> PrattParser<'a, &'a str, String> {
+        PrattParser::new(
+            |stream: &mut TokenStream<'a, &'a str>| match stream.peek() {
+                Some(token) if token.kind.chars().all(|c| c.is_alphanumeric()) => {
+                    Ok(stream.next().map(|token| token.kind.to_string()))
+                }
+                _ => Ok(None),
+            },
+        )
+        .prefix("-", 5, |_, a| format!("(-{})", a))
+        .group("(", ")", |a, _| a)
+        .infix("=", 0, Associativity::Right, |a, _, b| {
+            format!("({} = {})", a, b)
+        })
+        .ternary("?", ":", 1, |a, b, c| format!("({} ? {} : {})", a, b, c))
+        .infix("<", 2, Associativity::None, |a, _, b| {
+            format!("({} < {})", a, b)
+        })
+        .infix("+", 3, Associativity::Left, |a, _, b| {
+            format!("({} + {})", a, b)
+        })
+        .infix("*", 4, Associativity::Left, |a, _, b| {
+            format!("({} * {})", a, b)
+        })
+        .postfix("!", 6, |a, _| format!("({}!)", a))
+        .call("<(", ",", ")>", 7, |f, args, _| {
+            format!("{}[{}]", f, args.join(", "))
+        })
+        .index("[", "]", 7, |a, i, _| format!("{}.at({})", a, i))
+    }
+
+    fn parse(code: &str) -> Result<String, (String, Span)> {
+        parse_words(code, |stream| parser().parse(stream))
+    }
+
+    #[test]
+    fn test_pratt_precedence_and_associativity() {
+        assert_eq!(parse("1 + 2 * 3 + 4").unwrap(), "((1 + (2 * 3)) + 4)");
+        assert_eq!(parse("a = b = c").unwrap(), "(a = (b = c))");
+        assert_eq!(parse("- a ! * ( b + c )").unwrap(), "((-(a!)) * (b + c))");
+        assert_eq!(
+            parse("a < b ? x : y ? z : w").unwrap(),
+            "((a < b) ? x : (y ? z : w))"
+        );
+    }
+
+    #[test]
+    fn test_pratt_mixfix() {
+        assert_eq!(
+            parse("f <( a , b + c , )> [ 0 ]").unwrap(),
+            "f[a, (b + c)].at(0)"
+        );
+        assert_eq!(parse("f <( )>").unwrap(), "f[]");
+    }
+
+    #[test]
+    fn test_pratt_errors() {
+        assert_eq!(
+            parse("1 + * 2").unwrap_err(),
+            (
+                "expected expression, found `*`".to_string(),
+                Span::new(4, 5)
+            )
+        );
+        assert_eq!(
+            parse("1 +").unwrap_err(),
+            (
+                "expected expression, found end of file".to_string(),
+                Span::empty(3)
+            )
+        );
+        assert_eq!(
+            parse("( 1 + 2").unwrap_err(),
+            (
+                "expected `)`, found end of file".to_string(),
+                Span::empty(7)
+            )
+        );
+        assert_eq!(
+            parse("a < b < c").unwrap_err(),
+            ("`<` cannot be chained".to_string(), Span::new(6, 7))
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! This module provides utilities for recovering from syntax errors in parsers.
+//!
+//! After an error, a parser reports it, skips tokens up to a synchronization point and carries on,
+//! leaving an `ErrorNode` in the tree in place of the construct it failed to parse. This way,
+//! a file with several syntax errors reports all of them.
+
+use crate::{
+    diagnostics::Diagnostics,
+    error::Error,
+    parser::{Checkpoint, TokenStream},
+    span::{HasSpan, Span},
+    token::TokenKind,
+};
+
+/// A placeholder for a construct that failed to parse, spanning the tokens skipped to recover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorNode {
+    /// The span of the skipped tokens.
+    pub span: Span,
+}
+
+impl HasSpan for ErrorNode {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Describes where a parser can resume after an error.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{parser::TokenStream, recovery::Recovery, source::Source, span::Span, token::Token};
+///
+/// let source = Source::from_str("main.rn", "1 ( ; ) ; let");
+/// let tokens = ["1", "(", ";", ")", ";", "let"]
+///     .iter()
+///     .zip([0, 2, 4, 6, 8, 10])
+///     .map(|(kind, start)| Token::new(*kind, Span::new(start, start + kind.len())))
+///     .collect();
+///
+/// let recovery = Recovery::new()
+///     .with_terminator(";")
+///     .with_sync("let")
+///     .with_delimiter("(", ")");
+///
+/// let mut stream = TokenStream::new(&source, tokens);
+/// assert_eq!(recovery.skip(&mut stream), Span::new(0, 9));
+/// assert!(stream.check(&"let"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recovery<T> {
+    /// The kinds that end a construct, such as `;`. They are consumed when skipping.
+    terminators: Vec<T>,
+    /// The kinds that start a construct, such as keywords. They are not consumed when skipping.
+    sync: Vec<T>,
+    /// The pairs of delimiters, such as `(` and `)`, whose contents are skipped as a whole.
+    delimiters: Vec<(T, T)>,
+}
+
+impl<T: TokenKind> Recovery<T> {
+    /// Creates a new `Recovery` without any synchronization points.
+    pub fn new() -> Self {
+        Recovery {
+            terminators: Vec::new(),
+            sync: Vec::new(),
+            delimiters: Vec::new(),
+        }
+    }
+
+    /// Creates a new `Recovery`, adding a kind that ends a construct, such as `;`.
+    ///
+    /// Skipping stops after a terminator outside of delimiters.
+    pub fn with_terminator(mut self, kind: T) -> Self {
+        self.terminators.push(kind);
+        self
+    }
+
+    /// Creates a new `Recovery`, adding a kind that starts a construct, such as a keyword.
+    ///
+    /// Skipping stops before a synchronization token outside of delimiters.
+    pub fn with_sync(mut self, kind: T) -> Self {
+        self.sync.push(kind);
+        self
+    }
+
+    /// Creates a new `Recovery`, adding a pair of delimiters, such as `{` and `}`.
+    ///
+    /// Skipping does not stop inside delimiters, and stops before a closing delimiter
+    /// without a matching opening one, even inside other delimiters, as it belongs to an
+    /// enclosing construct. A closing delimiter matching an outer opening one also closes
+    /// the unclosed delimiters inside it.
+    pub fn with_delimiter(mut self, open: T, close: T) -> Self {
+        self.delimiters.push((open, close));
+        self
+    }
+
+    /// Skips tokens up to the next synchronization point, returning the span of the skipped tokens.
+    ///
+    /// If no token is skipped, the span is empty and located at the next token.
+    pub fn skip(&self, stream: &mut TokenStream<'_, T>) -> Span {
+        let start = stream.current_span();
+        let mut end: Option<Span> = None;
+        let mut depth: Vec<&T> = Vec::new();
+
+        while let Some(token) = stream.peek() {
+            let kind = &token.kind;
+
+            if depth.is_empty() {
+                if self.sync.iter().any(|sync| kind.same_kind(sync))
+                    || self
+                        .delimiters
+                        .iter()
+                        .any(|(_, close)| kind.same_kind(close))
+                {
+                    break;
+                }
+
+                if self
+                    .terminators
+                    .iter()
+                    .any(|terminator| kind.same_kind(terminator))
+                {
+                    end = stream.next().map(|token| token.span);
+                    break;
+                }
+            }
+
+            if let Some((_, close)) = self
+                .delimiters
+                .iter()
+                .find(|(open, _)| kind.same_kind(open))
+            {
+                depth.push(close);
+            } else if let Some(index) = depth.iter().rposition(|close| kind.same_kind(close)) {
+                depth.truncate(index);
+            } else if self
+                .delimiters
+                .iter()
+                .any(|(_, close)| kind.same_kind(close))
+            {
+                break;
+            }
+
+            end = stream.next().map(|token| token.span);
+        }
+
+        match end {
+            Some(end) => start.to(end),
+            None => Span::empty(start.start),
+        }
+    }
+
+    /// Reports the error, skips to the next synchronization point and returns an `ErrorNode`.
+    ///
+    /// `start` is the position where the failed construct started. If the stream is still there
+    /// after skipping, one token is consumed, so that loops parsing constructs always make progress.
+    pub fn recover<'a>(
+        &self,
+        stream: &mut TokenStream<'a, T>,
+        start: Checkpoint,
+        error: Error<'a>,
+        diagnostics: &mut Diagnostics<'a>,
+    ) -> ErrorNode {
+        diagnostics.push(error);
+
+        let skipped = self.skip(stream);
+        if stream.mark() == start
+            && let Some(token) = stream.next()
+        {
+            return ErrorNode { span: token.span };
+        }
+
+        ErrorNode { span: skipped }
+    }
+}
+
+impl<T: TokenKind> Default for Recovery<T> {
+    fn default() -> Self {
+        Recovery::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{source::Source, test_utils::token_stream};
+
+    #[derive(Debug, PartialEq)]
+    enum Statement {
+        Let,
+        Block(Vec<Statement>),
+        Error(ErrorNode),
+    }
+
+    /// Parses `let x ;` and `{ ... }` statements, recovering from errors.
+    fn statements<'a>(
+        stream: &mut TokenStream<'a, &'a str>,
+        diagnostics: &mut Diagnostics<'a>,
+        nested: bool,
+    ) -> Vec<Statement> {
+        let recovery = Recovery::new()
+            .with_terminator(";")
+            .with_sync("let")
+            .with_delimiter("{", "}");
+
+        let mut statements = Vec::new();
+        while !(stream.is_at_end() || nested && stream.check(&"}")) {
+            let start = stream.mark();
+            match statement(stream, diagnostics) {
+                Ok(statement) => statements.push(statement),
+                Err(error) => {
+                    let node = recovery.recover(stream, start, error, diagnostics);
+                    statements.push(Statement::Error(node));
+                }
+            }
+        }
+        statements
+    }
+
+    fn statement<'a>(
+        stream: &mut TokenStream<'a, &'a str>,
+        diagnostics: &mut Diagnostics<'a>,
+    ) -> Result<Statement, Error<'a>> {
+        if stream.eat(&"{").is_some() {
+            let body = statements(stream, diagnostics, true);
+            stream.expect(&"}", "expected `}`")?;
+            return Ok(Statement::Block(body));
+        }
+
+        stream.expect(&"let", "expected statement")?;
+        stream.expect(&"x", "expected name")?;
+        stream.expect(&";", "expected `;`")?;
+        Ok(Statement::Let)
+    }
+
+    #[test]
+    fn test_skip() {
+        let source = Source::from_str("test.rn", "a { ; } b ; c");
+        let recovery = Recovery::new()
+            .with_terminator(";")
+            .with_delimiter("{", "}");
+        let mut stream = token_stream(&source);
+
+        assert_eq!(recovery.skip(&mut stream), Span::new(0, 11));
+        assert!(stream.check(&"c"));
+
+        let source = Source::from_str("test.rn", "} a");
+        let mut stream = token_stream(&source);
+        assert_eq!(recovery.skip(&mut stream), Span::empty(0));
+
+        let recovery = recovery.with_delimiter("(", ")");
+        let source = Source::from_str("test.rn", "a ( } b");
+        let mut stream = token_stream(&source);
+        assert_eq!(recovery.skip(&mut stream), Span::new(0, 3));
+        assert!(stream.check(&"}"));
+
+        let source = Source::from_str("test.rn", "{ ( } ; b");
+        let mut stream = token_stream(&source);
+        assert_eq!(recovery.skip(&mut stream), Span::new(0, 7));
+        assert!(stream.check(&"b"));
+    }
+
+    #[test]
+    fn test_recover_reports_every_error() {
+        let source = Source::from_str(
+            "test.rn",
+            "let 1 ; let x ; } let x let x ; { let ; ( } 2 let x ;",
+        );
+        let mut stream = token_stream(&source);
+        let mut diagnostics = Diagnostics::new();
+
+        let statements = statements(&mut stream, &mut diagnostics, false);
+
+        let messages = diagnostics
+            .iter()
+            .map(|error| error.message())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            messages,
+            vec![
+                "expected name",
+                "expected statement",
+                "expected `;`",
+                "expected name",
+                "expected statement",
+                "expected statement",
+            ]
+        );
+        assert_eq!(statements.len(), 8);
+        assert_eq!(
+            statements[0],
+            Statement::Error(ErrorNode {
+                span: Span::new(4, 7)
+            })
+        );
+        assert_eq!(statements[7], Statement::Let);
+    }
+}
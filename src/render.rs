@@ -0,0 +1,904 @@
+//! This module provides the configuration and utilities used to render diagnostics as text.
+//!
+//! Diagnostics can be rendered into any `std::fmt::Write` or `std::io::Write`,
+//! with colors controlled by a `RenderConfig`.
+
+use std::{collections::BTreeMap, fmt, io::IsTerminal, sync::Arc};
+
+use colored::Color;
+
+use crate::{
+    catalog::{Catalog, Message, builtin_fallback},
+    error::{Label, LabelStyle, Suggestion},
+    source::Source,
+    span::{Span, location_to_line_col},
+    theme::Theme,
+};
+
+/// Controls whether rendered diagnostics contain ANSI color codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorChoice {
+    /// Colors are used if the output is a terminal and the `NO_COLOR` environment variable is not set.
+    #[default]
+    Auto,
+    /// Colors are always used.
+    Always,
+    /// Colors are never used.
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns `true` if colors should be used for an output that is (or is not) a terminal.
+    pub fn should_color(&self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    /// Returns `true` if colors should be used when writing to standard error.
+    pub fn should_color_stderr(&self) -> bool {
+        self.should_color(std::io::stderr().is_terminal())
+    }
+}
+
+/// Configures how diagnostics are rendered.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::render::{ColorChoice, RenderConfig};
+///
+/// let config = RenderConfig {
+///     color: ColorChoice::Never,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    /// Whether to use colors.
+    ///
+    /// When rendering into a `String` or an arbitrary writer, `ColorChoice::Auto` never uses colors,
+    /// as there is no way to tell whether the output ends up in a terminal.
+    pub color: ColorChoice,
+    /// The maximum number of columns of a source line to show.
+    ///
+    /// Longer lines are trimmed around the underlined text, with `...` marking the trimmed parts.
+    pub max_line_width: usize,
+    /// The catalog used to translate messages and built-in labels such as `error` and `-->`, if any.
+    ///
+    /// Messages missing from the catalog are rendered in English.
+    pub catalog: Option<Arc<dyn Catalog>>,
+    /// The colors and glyphs to render with.
+    pub theme: Theme,
+    /// The URL format of the OSC-8 hyperlinks printed on `file:line:col` locations, if any.
+    ///
+    /// `{path}`, `{line}` and `{column}` are replaced with the absolute path of the file and
+    /// the position of the location, such as `file://{path}` or `vscode://file/{path}:{line}:{column}`.
+    /// Terminals that support hyperlinks let the user open the location by clicking it.
+    /// Like colors, hyperlinks are only printed when coloring is enabled.
+    pub hyperlink_format: Option<String>,
+}
+
+impl RenderConfig {
+    /// Creates a new `RenderConfig` that never uses colors.
+    ///
+    /// This is useful for tests and log files.
+    pub fn plain() -> Self {
+        RenderConfig {
+            color: ColorChoice::Never,
+            ..Default::default()
+        }
+    }
+
+    /// Resolves the given message through the catalog, falling back to English.
+    pub fn translate(&self, message: &Message) -> String {
+        message.resolve(self.catalog.as_deref())
+    }
+
+    /// Returns the text of the built-in label with the given ID.
+    pub(crate) fn builtin(&self, id: &str) -> String {
+        self.translate(&Message::new(id, builtin_fallback(id)))
+    }
+
+    /// Returns the text of the built-in glyph with the given ID, falling back to the glyph of the theme.
+    pub(crate) fn glyph(&self, id: &str, glyph: &str) -> String {
+        self.translate(&Message::new(id, glyph))
+    }
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            color: ColorChoice::Auto,
+            max_line_width: 140,
+            catalog: None,
+            theme: Theme::default(),
+            hyperlink_format: None,
+        }
+    }
+}
+
+/// Represents the color and weight of a piece of rendered text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    /// The foreground color, if any.
+    pub color: Option<Color>,
+    /// Whether the text is bold.
+    pub bold: bool,
+}
+
+impl Style {
+    /// Creates a new `Style` with no color that is not bold.
+    pub const fn new() -> Self {
+        Style {
+            color: None,
+            bold: false,
+        }
+    }
+
+    /// Returns the style with the given foreground color.
+    pub const fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Returns the style made bold.
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Wraps the text in the ANSI escape codes for the style, if `enabled` is `true`.
+    pub fn paint(&self, text: &str, enabled: bool) -> String {
+        if !enabled || text.is_empty() || (self.color.is_none() && !self.bold) {
+            return text.to_string();
+        }
+
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if let Some(color) = self.color {
+            codes.push(color.to_fg_str().into_owned());
+        }
+
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+/// The number of columns a tab is expanded to.
+const TAB_WIDTH: usize = 4;
+/// Labels spanning more lines than this only show their first and last lines.
+const MAX_MULTILINE_LINES: usize = 4;
+
+/// The first and last line and column covered by a span.
+///
+/// The end column is inclusive. Zero-width spans cover the single column they point at,
+/// and spans ending with a newline end on the column of that newline.
+pub(crate) struct Location {
+    pub(crate) start_line: usize,
+    pub(crate) start_col: usize,
+    pub(crate) end_line: usize,
+    pub(crate) end_col: usize,
+}
+
+impl Location {
+    pub(crate) fn new(source: &Source, span: Span) -> Self {
+        let (start_line, start_col) = location_to_line_col(&source.code, span.start);
+        if span.is_empty() {
+            return Location {
+                start_line,
+                start_col,
+                end_line: start_line,
+                end_col: start_col,
+            };
+        }
+
+        let (mut end_line, mut end_col) = location_to_line_col(&source.code, span.end);
+        if end_col == 1 && end_line > start_line {
+            end_line -= 1;
+            end_col = line_text(source, end_line).chars().count() + 1;
+        } else {
+            end_col -= 1;
+        }
+
+        Location {
+            start_line,
+            start_col,
+            end_line,
+            end_col: end_col.max(1),
+        }
+    }
+}
+
+/// Returns the text of the given 1-based line, or an empty string if it does not exist.
+fn line_text<'s>(source: &'s Source, line: usize) -> &'s str {
+    source.code.lines().nth(line - 1).unwrap_or("")
+}
+
+/// A line of source code prepared for display.
+///
+/// Tabs are expanded to spaces, and lines longer than the maximum width are trimmed
+/// around a focus range, with an ellipsis marking the trimmed parts.
+struct DisplayLine {
+    /// The text to display.
+    text: String,
+    /// The expanded column (0-based) of each character of the line, followed by the expanded width.
+    columns: Vec<usize>,
+    /// The first expanded column shown.
+    window_start: usize,
+    /// The expanded column after the last one shown.
+    window_end: usize,
+    /// The number of columns taken by the leading ellipsis, if any.
+    prefix: usize,
+}
+
+impl DisplayLine {
+    /// Prepares a line for display, keeping the 1-based, inclusive character columns `focus` visible if possible.
+    fn new(line: &str, focus: Option<(usize, usize)>, max_width: usize, ellipsis: &str) -> Self {
+        let mut text = String::new();
+        let mut columns = Vec::new();
+        let mut width = 0;
+
+        for c in line.chars() {
+            columns.push(width);
+            if c == '\t' {
+                text += &" ".repeat(TAB_WIDTH);
+                width += TAB_WIDTH;
+            } else {
+                text.push(c);
+                width += 1;
+            }
+        }
+        columns.push(width);
+
+        let mut display = DisplayLine {
+            text,
+            columns,
+            window_start: 0,
+            window_end: width,
+            prefix: 0,
+        };
+
+        let max_width = max_width.max(1);
+        if width <= max_width {
+            return display;
+        }
+
+        let (focus_start, focus_end) = match focus {
+            Some((start, end)) => (display.expanded(start), display.expanded(end + 1)),
+            None => (0, 0),
+        };
+        let window_start = if focus_end - focus_start >= max_width {
+            focus_start
+        } else {
+            let padding = (max_width - (focus_end - focus_start)) / 2;
+            focus_start.saturating_sub(padding).min(width - max_width)
+        };
+        let window_end = (window_start + max_width).min(width);
+
+        let mut text = String::new();
+        if window_start > 0 {
+            text += ellipsis;
+        }
+        text += &display
+            .text
+            .chars()
+            .skip(window_start)
+            .take(window_end - window_start)
+            .collect::<String>();
+        if window_end < width {
+            text += ellipsis;
+        }
+
+        display.prefix = if window_start > 0 {
+            ellipsis.chars().count()
+        } else {
+            0
+        };
+        display.text = text;
+        display.window_start = window_start;
+        display.window_end = window_end;
+        display
+    }
+
+    /// Converts a 1-based character column to a 0-based expanded column.
+    ///
+    /// Columns past the end of the line (such as the newline) continue one column per character.
+    fn expanded(&self, col: usize) -> usize {
+        let index = col.saturating_sub(1);
+        let width = self.columns[self.columns.len() - 1];
+        match self.columns.get(index) {
+            Some(column) => *column,
+            None => width + (index + 1 - self.columns.len()),
+        }
+    }
+
+    /// Converts a 1-based character column to a 0-based column in the displayed text,
+    /// clamping columns outside of the shown window to its edges.
+    fn column(&self, col: usize) -> usize {
+        let expanded = self.expanded(col);
+        let expanded = if self.window_end < self.columns[self.columns.len() - 1] {
+            expanded.clamp(self.window_start, self.window_end)
+        } else {
+            expanded.max(self.window_start)
+        };
+        expanded - self.window_start + self.prefix
+    }
+}
+
+/// Returns the number of digits needed to print the line numbers of the given spans.
+pub(crate) fn line_number_width(source: &Source, spans: &[Span]) -> usize {
+    spans
+        .iter()
+        .map(|span| Location::new(source, *span).end_line.to_string().len())
+        .max()
+        .unwrap_or(1)
+}
+
+/// Returns the number of digits needed to print the line numbers of a suggestion applied to the source.
+pub(crate) fn suggestion_line_number_width(source: &Source, suggestion: &Suggestion) -> usize {
+    let (start_line, _) = location_to_line_col(&source.code, suggestion.span.start);
+    (start_line + suggestion.replacement.matches('\n').count())
+        .to_string()
+        .len()
+}
+
+/// Percent-encodes a path for use in a URL, keeping `/` and unreserved characters as they are.
+pub(crate) fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded += &format!("%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// The part of a label that is underlined on a single line.
+struct Annotation<'l> {
+    /// The first underlined column (1-based).
+    start_col: usize,
+    /// The last underlined column (1-based, inclusive).
+    end_col: usize,
+    /// How the underline is rendered.
+    style: LabelStyle,
+    /// The message of the label, present only on the last line of the label.
+    message: Option<&'l str>,
+}
+
+/// Writes the pieces of a rendered diagnostic into an output.
+pub(crate) struct Renderer<'w> {
+    /// The output to write into.
+    out: &'w mut dyn fmt::Write,
+    /// Whether to use colors.
+    colored: bool,
+    /// The width of the line number gutter.
+    number_of_spaces: usize,
+    /// The maximum number of columns of a source line to show.
+    max_line_width: usize,
+    /// The colors and glyphs to render with.
+    theme: Theme,
+    /// The URL format of the hyperlinks printed on locations, if any.
+    hyperlink_format: Option<&'w str>,
+}
+
+impl<'w> Renderer<'w> {
+    pub(crate) fn new(
+        out: &'w mut dyn fmt::Write,
+        config: &'w RenderConfig,
+        colored: bool,
+        number_of_spaces: usize,
+    ) -> Self {
+        Renderer {
+            out,
+            colored,
+            number_of_spaces,
+            max_line_width: config.max_line_width,
+            theme: config.theme,
+            hyperlink_format: config.hyperlink_format.as_deref(),
+        }
+    }
+
+    /// Paints the text in the given style, if colors are enabled.
+    pub(crate) fn paint(&self, text: &str, style: Style) -> String {
+        style.paint(text, self.colored)
+    }
+
+    /// Writes a line of raw text.
+    pub(crate) fn line(&mut self, text: &str) -> fmt::Result {
+        writeln!(self.out, "{}", text)
+    }
+
+    /// Writes an empty gutter line.
+    pub(crate) fn empty_gutter(&mut self) -> fmt::Result {
+        let bar = self.paint(self.theme.glyphs.gutter, self.theme.styles.accent);
+        writeln!(self.out, "{} {}", " ".repeat(self.number_of_spaces), bar)
+    }
+
+    /// Writes a `= prefix message` footer line, such as a note.
+    pub(crate) fn footer(&mut self, prefix: Option<&str>, message: &str) -> fmt::Result {
+        let equals = self.paint(self.theme.glyphs.footer, self.theme.styles.accent);
+        match prefix {
+            Some(prefix) => {
+                let prefix = self.paint(prefix, self.theme.styles.emphasis);
+                writeln!(
+                    self.out,
+                    "{} {} {} {}",
+                    " ".repeat(self.number_of_spaces),
+                    equals,
+                    prefix,
+                    message
+                )
+            }
+            None => writeln!(
+                self.out,
+                "{} {} {}",
+                " ".repeat(self.number_of_spaces),
+                equals,
+                message
+            ),
+        }
+    }
+
+    /// Writes the `file:line:col` location of a span, prefixed with the given arrow.
+    ///
+    /// The location is printed as a hyperlink if a hyperlink format is configured and colors are enabled.
+    pub(crate) fn location(&mut self, source: &Source, span: Span, arrow: &str) -> fmt::Result {
+        let location = Location::new(source, span);
+        let arrow = self.paint(arrow, self.theme.styles.accent);
+
+        let mut text = format!(
+            "{}:{}:{}",
+            source.filename, location.start_line, location.start_col
+        );
+        if location.start_line != location.end_line {
+            text += &format!("-{}:{}", location.end_line, location.end_col);
+        } else if location.start_col != location.end_col {
+            text += &format!("-{}", location.end_col);
+        }
+
+        if let Some(format) = self.hyperlink_format
+            && self.colored
+        {
+            let path = std::path::absolute(source.filename)
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| source.filename.to_string());
+            let url = format
+                .replace("{path}", &percent_encode(&path))
+                .replace("{line}", &location.start_line.to_string())
+                .replace("{column}", &location.start_col.to_string());
+            text = format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text);
+        }
+
+        writeln!(
+            self.out,
+            "{}{} {}",
+            " ".repeat(self.number_of_spaces),
+            arrow,
+            text
+        )
+    }
+
+    /// Writes a gutter, optionally with a line number, followed by the given text.
+    fn gutter_line(&mut self, line_number: Option<usize>, text: &str) -> fmt::Result {
+        let bar = self.paint(self.theme.glyphs.gutter, self.theme.styles.accent);
+        match line_number {
+            Some(line_number) if self.theme.line_numbers => {
+                let number = self.paint(&line_number.to_string(), self.theme.styles.accent);
+                write!(
+                    self.out,
+                    "{}{} {}",
+                    number,
+                    " ".repeat(self.number_of_spaces - line_number.to_string().len()),
+                    bar
+                )?;
+            }
+            _ => write!(self.out, "{} {}", " ".repeat(self.number_of_spaces), bar)?,
+        }
+
+        if text.is_empty() {
+            writeln!(self.out)
+        } else {
+            writeln!(self.out, " {}", text)
+        }
+    }
+
+    /// Writes the lines changed by a suggestion, with the suggestion applied.
+    ///
+    /// Inserted text is marked with `+` and replaced text with `~` (with the default glyphs).
+    /// Removals are shown as the original lines with the removed text underlined like a secondary label.
+    pub(crate) fn suggestion(&mut self, source: &Source, suggestion: &Suggestion) -> fmt::Result {
+        let span = suggestion.span;
+        if suggestion.replacement.is_empty() {
+            let label = Label {
+                span,
+                message: None,
+                style: LabelStyle::Secondary,
+            };
+            return self.snippet(source, &[label], self.theme.styles.accent);
+        }
+
+        if source.code.get(span.start..span.end).is_none() {
+            return Ok(());
+        }
+
+        let line_start = source.code[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source.code[span.end..]
+            .find('\n')
+            .map_or(source.code.len(), |i| span.end + i);
+        let (start_line, _) = location_to_line_col(&source.code, span.start);
+
+        let before = &source.code[line_start..span.start];
+        let after = &source.code[span.end..line_end];
+        let patched = format!("{}{}{}", before, suggestion.replacement, after);
+
+        let marker = if span.is_empty() {
+            self.theme.glyphs.insertion
+        } else {
+            self.theme.glyphs.replacement
+        };
+        let marked_start = before.chars().count();
+        let marked_end = marked_start + suggestion.replacement.chars().count();
+
+        let mut offset = 0;
+        for (index, line) in patched.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let length = line.chars().count();
+
+            let start = marked_start.max(offset) - offset;
+            let end = marked_end.min(offset + length).saturating_sub(offset);
+            let focus = (start < end).then_some((start + 1, end));
+
+            let display =
+                DisplayLine::new(line, focus, self.max_line_width, self.theme.glyphs.ellipsis);
+            self.gutter_line(Some(start_line + index), &display.text)?;
+
+            if let Some((start, end)) = focus {
+                let display_start = display.column(start);
+                let display_end = display.column(end + 1).max(display_start + 1);
+                let markers = self.paint(
+                    &marker.repeat(display_end - display_start),
+                    self.theme.styles.accent,
+                );
+                self.gutter_line(None, &format!("{}{}", " ".repeat(display_start), markers))?;
+            }
+
+            offset += length + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the lines covered by the given labels as a single snippet.
+    ///
+    /// Primary labels are underlined with `^` in the `primary` style, secondary labels with `-`
+    /// in the accent style (with the default theme). Label messages are printed after the last
+    /// underline, or below it with `|` connectors when several labels on the same line have messages.
+    /// Gaps of more than one line between labelled lines are elided with `...`.
+    pub(crate) fn snippet(
+        &mut self,
+        source: &Source,
+        labels: &[Label],
+        primary: Style,
+    ) -> fmt::Result {
+        let theme = self.theme;
+        let label_style = |style: LabelStyle| match style {
+            LabelStyle::Primary => primary,
+            LabelStyle::Secondary => theme.styles.accent,
+        };
+
+        let mut annotations: BTreeMap<usize, Vec<Annotation>> = BTreeMap::new();
+
+        for label in labels {
+            let location = Location::new(source, label.span);
+
+            let line_count = location.end_line - location.start_line + 1;
+            let lines = (location.start_line..=location.end_line).filter(|line| {
+                line_count <= MAX_MULTILINE_LINES
+                    || *line == location.start_line
+                    || *line == location.end_line
+            });
+
+            for line in lines {
+                let length = line_text(source, line).chars().count();
+                let start_col = if line == location.start_line {
+                    location.start_col
+                } else {
+                    1
+                };
+                let end_col = if line == location.end_line {
+                    location.end_col
+                } else {
+                    length.max(start_col)
+                };
+
+                annotations.entry(line).or_default().push(Annotation {
+                    start_col,
+                    end_col: end_col.max(start_col),
+                    style: label.style,
+                    message: if line == location.end_line {
+                        label.message.as_deref()
+                    } else {
+                        None
+                    },
+                });
+            }
+        }
+
+        let mut previous_line: Option<usize> = None;
+        let lines = annotations.keys().copied().collect::<Vec<usize>>();
+
+        for line_number in lines {
+            if let Some(previous) = previous_line {
+                if line_number - previous == 2 {
+                    let display = DisplayLine::new(
+                        line_text(source, previous + 1),
+                        None,
+                        self.max_line_width,
+                        theme.glyphs.ellipsis,
+                    );
+                    self.gutter_line(Some(previous + 1), &display.text)?;
+                } else if line_number - previous > 2 {
+                    let dots = self.paint(theme.glyphs.ellipsis, theme.styles.accent);
+                    self.line(&dots)?;
+                }
+            }
+            previous_line = Some(line_number);
+
+            let mut line_annotations = annotations.remove(&line_number).unwrap_or_default();
+            let focus = line_annotations
+                .iter()
+                .map(|annotation| (annotation.start_col, annotation.end_col))
+                .reduce(|(a_start, a_end), (b_start, b_end)| {
+                    (a_start.min(b_start), a_end.max(b_end))
+                });
+
+            let display = DisplayLine::new(
+                line_text(source, line_number),
+                focus,
+                self.max_line_width,
+                theme.glyphs.ellipsis,
+            );
+            self.gutter_line(Some(line_number), &display.text)?;
+
+            for annotation in line_annotations.iter_mut() {
+                let start = display.column(annotation.start_col);
+                let end = display.column(annotation.end_col + 1).max(start + 1);
+                annotation.start_col = start + 1;
+                annotation.end_col = end;
+            }
+            line_annotations.sort_by_key(|annotation| (annotation.start_col, annotation.end_col));
+
+            let width = line_annotations
+                .iter()
+                .map(|annotation| annotation.end_col)
+                .max()
+                .unwrap_or(0);
+            let mut markers: Vec<Option<LabelStyle>> = vec![None; width];
+            for style in [LabelStyle::Secondary, LabelStyle::Primary] {
+                for annotation in line_annotations.iter().filter(|a| a.style == style) {
+                    for marker in &mut markers[annotation.start_col - 1..annotation.end_col] {
+                        *marker = Some(style);
+                    }
+                }
+            }
+
+            let mut underline = String::new();
+            for run in markers.chunk_by(|a, b| a == b) {
+                underline += &match run[0] {
+                    Some(style @ LabelStyle::Primary) => {
+                        self.paint(&theme.glyphs.primary.repeat(run.len()), label_style(style))
+                    }
+                    Some(style @ LabelStyle::Secondary) => self.paint(
+                        &theme.glyphs.secondary.repeat(run.len()),
+                        label_style(style),
+                    ),
+                    None => " ".repeat(run.len()),
+                };
+            }
+
+            let mut messages = line_annotations
+                .iter()
+                .filter(|annotation| annotation.message.is_some())
+                .collect::<Vec<&Annotation>>();
+
+            // The rightmost message is printed inline, unless another label ends after it starts.
+            let inline = match messages.last() {
+                Some(last)
+                    if messages
+                        .iter()
+                        .all(|a| a.end_col < last.start_col || std::ptr::eq(*a, *last)) =>
+                {
+                    messages.pop()
+                }
+                _ => None,
+            };
+
+            match inline {
+                Some(annotation) => {
+                    let message = self.paint(
+                        annotation.message.unwrap_or_default(),
+                        label_style(annotation.style),
+                    );
+                    self.gutter_line(None, &format!("{} {}", underline, message))?;
+                }
+                None => self.gutter_line(None, &underline)?,
+            }
+
+            if messages.is_empty() {
+                continue;
+            }
+
+            let colored = self.colored;
+            let connectors = |count: usize| {
+                let mut row = String::new();
+                let mut column = 1;
+                for annotation in messages.iter().take(count) {
+                    row += &" ".repeat(annotation.start_col - column);
+                    row += &label_style(annotation.style).paint(theme.glyphs.connector, colored);
+                    column = annotation.start_col + 1;
+                }
+                (row, column)
+            };
+
+            let (row, _) = connectors(messages.len());
+            self.gutter_line(None, &row)?;
+            for index in (0..messages.len()).rev() {
+                let (row, column) = connectors(index);
+                let annotation = messages[index];
+                let message = self.paint(
+                    annotation.message.unwrap_or_default(),
+                    label_style(annotation.style),
+                );
+                self.gutter_line(
+                    None,
+                    &format!(
+                        "{}{}{}",
+                        row,
+                        " ".repeat(annotation.start_col - column),
+                        message
+                    ),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, source::Source, theme::Glyphs};
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Always.should_color(false));
+        assert!(!ColorChoice::Never.should_color(true));
+        assert!(!ColorChoice::Auto.should_color(false));
+    }
+
+    fn render(code: &str, span: Span, max_line_width: usize) -> String {
+        let source = Source::from_str("test.rs", code);
+        let error = Error::new("Syntax error".to_string(), &source, span)
+            .with_primary_label("here".to_string());
+        let config = RenderConfig {
+            max_line_width,
+            ..RenderConfig::plain()
+        };
+
+        let mut output = String::new();
+        error.render(&mut output, &config).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_render_edge_cases() {
+        assert_eq!(
+            render("let x = 10", Span::empty(10), 140),
+            "error: Syntax error\n --> test.rs:1:11\n  |\n1 | let x = 10\n  |           ^ here\n"
+        );
+        assert_eq!(
+            render("let x = 10\n", Span::new(8, 11), 140),
+            "error: Syntax error\n --> test.rs:1:9-11\n  |\n1 | let x = 10\n  |         ^^^ here\n"
+        );
+        assert_eq!(
+            render("", Span::empty(0), 140),
+            "error: Syntax error\n --> test.rs:1:1\n  |\n1 |\n  | ^ here\n"
+        );
+        assert_eq!(
+            render("let x = 10;\r\nlet y = 20;\r\n", Span::new(17, 19), 140),
+            "error: Syntax error\n --> test.rs:2:5-6\n  |\n2 | let y = 20;\n  |     ^^ here\n"
+        );
+    }
+
+    #[test]
+    fn test_render_long_multiline_span() {
+        let code = "fn main() {\n    a\n    b\n    c\n    d\n    e\n}";
+        assert_eq!(
+            render(code, Span::new(0, code.len()), 140),
+            "error: Syntax error\n --> test.rs:1:1-7:1\n  |\n1 | fn main() {\n  | ^^^^^^^^^^^\n...\n7 | }\n  | ^ here\n"
+        );
+    }
+
+    #[test]
+    fn test_render_tabs_and_long_lines() {
+        assert_eq!(
+            render("\tlet\tx = 10", Span::new(5, 6), 140),
+            "error: Syntax error\n --> test.rs:1:6\n  |\n1 |     let    x = 10\n  |            ^ here\n"
+        );
+
+        let code = format!("let x = {}y{};", "1 + ".repeat(30), " + 1".repeat(30));
+        let start = code.find('y').unwrap();
+        let output = render(&code, Span::new(start, start + 1), 20);
+        assert_eq!(
+            output,
+            "error: Syntax error\n --> test.rs:1:129\n  |\n1 | ... 1 + 1 + y + 1 + 1 +...\n  |             ^ here\n"
+        );
+    }
+
+    #[test]
+    fn test_render_theme() {
+        let source = Source::from_str("test.rs", "let x = 10");
+        let error = Error::new("Syntax error".to_string(), &source, Span::new(4, 5))
+            .with_label(Span::new(8, 10), "value".to_string())
+            .with_note("Check the syntax".to_string());
+        let config = RenderConfig {
+            theme: Theme::ascii()
+                .with_glyphs(Glyphs::unicode())
+                .with_line_numbers(false),
+            ..RenderConfig::plain()
+        };
+
+        let mut output = String::new();
+        error.render(&mut output, &config).unwrap();
+        assert_eq!(
+            output,
+            "error: Syntax error\n ╭─▶ test.rs:1:5\n  │\n  │ let x = 10\n  │     ━   ── value\n  │\n  • note: Check the syntax\n"
+        );
+    }
+
+    #[test]
+    fn test_render_hyperlinks() {
+        let source = Source::from_str("my file#1.rs", "let x = 10");
+        let error = Error::new("Syntax error".to_string(), &source, Span::new(4, 5));
+        let mut config = RenderConfig {
+            hyperlink_format: Some("editor://{path}:{line}:{column}".to_string()),
+            ..RenderConfig::plain()
+        };
+
+        let mut output = String::new();
+        error.render(&mut output, &config).unwrap();
+        assert!(!output.contains("\x1b"));
+        assert!(output.contains(" --> my file#1.rs:1:5\n"));
+
+        config.color = ColorChoice::Always;
+        let mut output = String::new();
+        error.render(&mut output, &config).unwrap();
+
+        let path = std::path::absolute("my file#1.rs").unwrap();
+        let path = percent_encode(&path.to_string_lossy());
+        assert!(path.ends_with("/my%20file%231.rs"));
+        let link = format!(
+            "\x1b]8;;editor://{}:1:5\x1b\\my file#1.rs:1:5\x1b]8;;\x1b\\",
+            path
+        );
+        assert!(output.contains(&link));
+        assert_eq!(percent_encode("/a b/é.rs"), "/a%20b/%C3%A9.rs");
+    }
+
+    #[test]
+    fn test_style_paint() {
+        let style = Style::new().color(Color::Red).bold();
+        assert_eq!(style.paint("error", true), "\x1b[1;31merror\x1b[0m");
+        assert_eq!(style.paint("error", false), "error");
+        assert_eq!(Style::new().paint("error", true), "error");
+    }
+}
@@ -1,5 +1,7 @@
 //! This module defines the `Source` struct, which encapsulates source code and its associated filename.
 
+use crate::loader::{DiskLoader, FileLoader};
+
 /// Represents source code along with its filename.
 #[derive(Debug)]
 pub struct Source<'a> {
@@ -12,12 +14,29 @@ pub struct Source<'a> {
 impl<'a> Source<'a> {
     /// Creates a new `Source` instance, reading the source code from the given filename.
     pub fn new(filename: &'a str) -> Result<Self, std::io::Error> {
-        let code = std::fs::read_to_string(filename)?;
+        Source::load(&DiskLoader, filename)
+    }
+
+    /// Creates a new `Source` instance, reading the source code from the given filename using `loader`.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{loader::MemoryLoader, source::Source};
+    ///
+    /// let mut loader = MemoryLoader::new();
+    /// loader.insert("main.rn", "let x = 10;");
+    ///
+    /// let source = Source::load(&loader, "main.rn").unwrap();
+    /// assert_eq!(source.code, "let x = 10;");
+    /// ```
+    pub fn load(loader: &impl FileLoader, filename: &'a str) -> Result<Self, std::io::Error> {
+        let code = loader.read(filename)?;
         Ok(Source { filename, code })
     }
 
-    /// Creates a new `Source` instance from a string slice.
-    pub fn from_str(filename: &'a str, code: &'a str) -> Self {
+    /// Creates a new `Source` instance from a string slice, copying the code.
+    pub fn from_str(filename: &'a str, code: &str) -> Self {
         Source {
             filename,
             code: code.to_string(),
@@ -28,18 +47,24 @@ impl<'a> Source<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::loader::MemoryLoader;
 
     #[test]
     fn test_source_new() {
-        let filename = "test_file.txt";
-        let code = "fn main() { println!(\"Hello, world!\"); }";
-        std::fs::write(filename, code).unwrap();
+        let error = Source::new("missing/test_file.txt").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    }
 
-        let source = Source::new(filename).unwrap();
-        assert_eq!(source.filename, filename);
-        assert_eq!(source.code, code);
+    #[test]
+    fn test_source_load() {
+        let mut loader = MemoryLoader::new();
+        loader.insert("test_file.txt", "fn main() {}");
+
+        let source = Source::load(&loader, "test_file.txt").unwrap();
+        assert_eq!(source.filename, "test_file.txt");
+        assert_eq!(source.code, "fn main() {}");
 
-        std::fs::remove_file(filename).unwrap();
+        assert!(Source::load(&loader, "missing.txt").is_err());
     }
 
     #[test]
@@ -1,8 +1,18 @@
 //! This module defines the `Span` struct, which represents a span of text in a source file.
-//! It also provides utilities for working with spans.
+//! It also provides utilities for working with spans, such as the `Spanned` wrapper and the `HasSpan` trait.
+
+use std::{
+    error::Error as StdError,
+    fmt,
+    ops::{Deref, DerefMut, Range},
+};
+
+use crate::source::Source;
 
 /// A `Span` represents a contiguous region in a source file, defined by its start and end byte indices.
-#[derive(Debug)]
+///
+/// A span may be zero-width (`start == end`), which is useful for positions such as `EOF` or insertion points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct Span {
     /// The starting byte index of the span (inclusive).
     pub start: usize,
@@ -15,11 +25,253 @@ impl Span {
     ///
     /// # Panics
     ///
-    /// Panics if `start` is greater than or equal to `end`.
+    /// Panics if `start` is greater than `end`.
     pub fn new(start: usize, end: usize) -> Self {
-        assert!(start < end, "Span start must be less than end");
+        assert!(start <= end, "Span start must not be greater than end");
         Span { start, end }
     }
+
+    /// Creates a new `Span` from the given start and end byte indices,
+    /// returning `None` if `start` is greater than `end`.
+    pub fn try_new(start: usize, end: usize) -> Option<Self> {
+        if start <= end {
+            Some(Span { start, end })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a zero-width `Span` at the given byte index.
+    pub fn empty(at: usize) -> Self {
+        Span { start: at, end: at }
+    }
+
+    /// Returns the length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if the span is zero-width.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns a span from the start of `self` to the end of `other`.
+    ///
+    /// This is useful for building a span for a node from its first and last tokens.
+    /// If `other` starts before `self`, the spans are out of order, and the span covering both
+    /// is returned instead, as with `merge`.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::span::Span;
+    ///
+    /// let first = Span::new(0, 3);
+    /// let last = Span::new(8, 10);
+    /// assert_eq!(first.to(last), Span::new(0, 10));
+    /// ```
+    pub fn to(self, other: Span) -> Span {
+        if other.start < self.start {
+            return self.merge(other);
+        }
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
+
+    /// Returns the smallest span that covers both `self` and `other`.
+    pub fn merge(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Returns the region covered by both `self` and `other`, if any.
+    ///
+    /// Spans that only touch at their edges intersect in a zero-width span.
+    pub fn intersect(self, other: Span) -> Option<Span> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        Span::try_new(start, end)
+    }
+
+    /// Returns `true` if `other` lies entirely within `self`.
+    pub fn contains(&self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Returns `true` if the byte index lies within the span.
+    pub fn contains_offset(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    /// Returns `true` if `self` and `other` share at least one byte.
+    pub fn overlaps(&self, other: Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Returns the span moved forward by `offset` bytes.
+    ///
+    /// This is useful for mapping spans of a substring back into the containing string.
+    pub fn shift(self, offset: usize) -> Span {
+        Span {
+            start: self.start + offset,
+            end: self.end + offset,
+        }
+    }
+
+    /// Returns the text covered by the span in the given source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span is out of bounds or does not lie on UTF-8 character boundaries.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use runic_kit::{source::Source, span::Span};
+    ///
+    /// let source = Source::from_str("main.rn", "let x = 10;");
+    /// assert_eq!(Span::new(4, 5).text(&source), "x");
+    /// ```
+    pub fn text<'s>(&self, source: &'s Source) -> &'s str {
+        &source.code[self.start..self.end]
+    }
+}
+
+/// The error returned when converting an inverted range, such as `5..2`, into a `Span`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvalidSpan(pub Range<usize>);
+
+impl fmt::Display for InvalidSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "span start {} is greater than end {}",
+            self.0.start, self.0.end
+        )
+    }
+}
+
+impl StdError for InvalidSpan {}
+
+impl TryFrom<Range<usize>> for Span {
+    type Error = InvalidSpan;
+
+    fn try_from(range: Range<usize>) -> Result<Self, Self::Error> {
+        Span::try_new(range.start, range.end).ok_or(InvalidSpan(range))
+    }
+}
+
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+
+/// Defines the interface for values that have a location in a source file.
+///
+/// This lets tokens, AST nodes and errors be passed to the same span-based utilities.
+pub trait HasSpan {
+    /// Returns the span of the value.
+    fn span(&self) -> Span;
+}
+
+impl HasSpan for Span {
+    fn span(&self) -> Span {
+        *self
+    }
+}
+
+impl<T: HasSpan + ?Sized> HasSpan for &T {
+    fn span(&self) -> Span {
+        (**self).span()
+    }
+}
+
+impl<T: HasSpan + ?Sized> HasSpan for Box<T> {
+    fn span(&self) -> Span {
+        (**self).span()
+    }
+}
+
+/// Represents a value paired with the span it was parsed from.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::span::{Span, Spanned};
+///
+/// let number = Spanned::new("10", Span::new(8, 10));
+/// let number = number.map(|text| text.parse::<i64>().unwrap());
+/// assert_eq!(*number, 10);
+/// assert_eq!(number.span, Span::new(8, 10));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    /// The wrapped value.
+    pub node: T,
+    /// The span in the source code where the value is located.
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    /// Creates a new `Spanned`.
+    pub fn new(node: T, span: Span) -> Self {
+        Spanned { node, span }
+    }
+
+    /// Maps the wrapped value, keeping the span.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned {
+            node: f(self.node),
+            span: self.span,
+        }
+    }
+
+    /// Borrows the wrapped value, keeping the span.
+    pub fn as_ref(&self) -> Spanned<&T> {
+        Spanned {
+            node: &self.node,
+            span: self.span,
+        }
+    }
+
+    /// Mutably borrows the wrapped value, keeping the span.
+    pub fn as_mut(&mut self) -> Spanned<&mut T> {
+        Spanned {
+            node: &mut self.node,
+            span: self.span,
+        }
+    }
+
+    /// Consumes the `Spanned`, returning the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.node
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+impl<T> DerefMut for Spanned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.node
+    }
+}
+
+impl<T> HasSpan for Spanned<T> {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 /// Converts a byte index in the source string to a (line, column) tuple.
@@ -28,6 +280,9 @@ impl Span {
 ///
 /// Column of the newline character is + 1 of the last character in the line.
 ///
+/// An index inside a multi-byte character is treated as the start of that character,
+/// and an index past the end of the source gives the position after the last character.
+///
 /// # Usage
 ///
 /// ```rust
@@ -43,7 +298,7 @@ pub fn location_to_line_col(source: &str, index: usize) -> (usize, usize) {
     let mut col = 1;
 
     for (i, c) in source.char_indices() {
-        if i == index {
+        if i >= index {
             break;
         }
 
@@ -70,11 +325,71 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Span start must be less than end")]
+    #[should_panic(expected = "Span start must not be greater than end")]
     fn test_span_new_invalid() {
         Span::new(10, 5);
     }
 
+    #[test]
+    fn test_span_try_new() {
+        assert_eq!(Span::try_new(5, 10), Some(Span::new(5, 10)));
+        assert_eq!(Span::try_new(5, 5), Some(Span::empty(5)));
+        assert_eq!(Span::try_new(10, 5), None);
+
+        assert_eq!(Span::try_from(2..5), Ok(Span::new(2, 5)));
+        let inverted = Range { start: 5, end: 2 };
+        assert_eq!(Span::try_from(inverted.clone()), Err(InvalidSpan(inverted)));
+        assert_eq!(Range::from(Span::new(2, 5)), 2..5);
+    }
+
+    #[test]
+    fn test_span_algebra() {
+        let a = Span::new(0, 5);
+        let b = Span::new(3, 8);
+        let c = Span::new(5, 10);
+
+        assert_eq!(a.len(), 5);
+        assert!(Span::empty(3).is_empty());
+
+        assert_eq!(a.to(c), Span::new(0, 10));
+        assert_eq!(c.to(a), Span::new(0, 10));
+        assert_eq!(Span::new(6, 8).to(Span::new(0, 2)), Span::new(0, 8));
+        assert_eq!(c.merge(a), Span::new(0, 10));
+
+        assert_eq!(a.intersect(b), Some(Span::new(3, 5)));
+        assert_eq!(a.intersect(c), Some(Span::empty(5)));
+        assert_eq!(a.intersect(Span::new(7, 9)), None);
+
+        assert!(a.overlaps(b));
+        assert!(!a.overlaps(c));
+
+        assert!(a.contains(Span::new(1, 4)));
+        assert!(a.contains(Span::empty(5)));
+        assert!(!a.contains(b));
+        assert!(a.contains_offset(4));
+        assert!(!a.contains_offset(5));
+
+        assert_eq!(a.shift(10), Span::new(10, 15));
+    }
+
+    #[test]
+    fn test_span_text() {
+        let source = Source::from_str("test.rs", "let x = 10;");
+        assert_eq!(Span::new(0, 3).text(&source), "let");
+        assert_eq!(Span::empty(3).text(&source), "");
+    }
+
+    #[test]
+    fn test_spanned() {
+        let mut spanned = Spanned::new(String::from("x"), Span::new(4, 5));
+        spanned.push('y');
+
+        assert_eq!(spanned.len(), 2);
+        assert_eq!(spanned.as_ref().map(|s| s.len()).node, 2);
+        assert_eq!(spanned.span(), Span::new(4, 5));
+        assert_eq!(spanned.into_inner(), "xy");
+    }
+
     #[test]
     fn test_location_to_line_col() {
         let source = "Hello\nWorld";
@@ -0,0 +1,38 @@
+//! This module provides fixtures shared by the unit tests of the parsing modules.
+
+use crate::{
+    error::Error,
+    parser::TokenStream,
+    source::Source,
+    span::{HasSpan, Span},
+    token::Token,
+};
+
+/// Splits the code on spaces, using each word as the kind of a token.
+pub(crate) fn tokenize(code: &str) -> Vec<Token<&str>> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    for word in code.split(' ') {
+        if !word.is_empty() {
+            tokens.push(Token::new(word, Span::new(offset, offset + word.len())));
+        }
+        offset += word.len() + 1;
+    }
+    tokens
+}
+
+/// Creates a `TokenStream` over the words of the source, as split by `tokenize`.
+pub(crate) fn token_stream<'a>(source: &'a Source<'a>) -> TokenStream<'a, &'a str> {
+    TokenStream::new(source, tokenize(&source.code))
+}
+
+/// Parses the words of the code with `parse`, returning the output, or the message and span of
+/// the error.
+pub(crate) fn parse_words<O>(
+    code: &str,
+    parse: impl for<'a> FnOnce(&mut TokenStream<'a, &'a str>) -> Result<O, Error<'a>>,
+) -> Result<O, (String, Span)> {
+    let source = Source::from_str("test.rn", code);
+    let mut stream = token_stream(&source);
+    parse(&mut stream).map_err(|error| (error.message().to_string(), error.span()))
+}
@@ -0,0 +1,404 @@
+//! This module provides a harness for testing diagnostics against inline annotations.
+//!
+//! Test files mark the diagnostics they expect with comments in the style of rustc's UI tests:
+//!
+//! ```text
+//! let x = 10 //~ ERROR expected `;`
+//! let y = z
+//! //~^ ERROR unknown name `z`
+//! //~| WARNING unused variable `y`
+//! ```
+//!
+//! `//~` refers to its own line, each `^` moves one line up, and `//~|` refers to the same line
+//! as the previous annotation. The message of an annotation only needs to be part of the message
+//! of the diagnostic, and may be omitted to match any message.
+
+use std::{error::Error as StdError, fmt};
+
+use crate::{
+    error::{Error, Severity},
+    source::Source,
+    span::{HasSpan, Span, location_to_line_col},
+};
+
+/// A diagnostic expected (or produced) on a line of a test file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expectation {
+    /// The line of the diagnostic (1-based).
+    pub line: usize,
+    /// The severity of the diagnostic.
+    pub severity: Severity,
+    /// The message of the diagnostic, or a part of it.
+    pub message: String,
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {}",
+            self.line,
+            self.severity.as_str().to_uppercase(),
+            self.message
+        )
+    }
+}
+
+/// Describes how the diagnostics of a test file differ from its annotations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The filename of the test file.
+    pub filename: String,
+    /// The annotations that matched a diagnostic.
+    pub matched: Vec<Expectation>,
+    /// The annotations that no diagnostic matched.
+    pub missing: Vec<Expectation>,
+    /// The diagnostics that no annotation matched.
+    pub unexpected: Vec<Expectation>,
+}
+
+impl fmt::Display for Mismatch {
+    /// Formats the mismatch as a diff, with missing diagnostics prefixed by `-`
+    /// and unexpected ones by `+`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "diagnostics did not match the annotations in `{}`:",
+            self.filename
+        )?;
+
+        let mut entries = self
+            .matched
+            .iter()
+            .map(|expectation| (' ', expectation))
+            .chain(self.missing.iter().map(|expectation| ('-', expectation)))
+            .chain(self.unexpected.iter().map(|expectation| ('+', expectation)))
+            .collect::<Vec<(char, &Expectation)>>();
+        entries.sort_by_key(|(_, expectation)| expectation.line);
+
+        for (marker, expectation) in entries {
+            writeln!(f, "{} {}", marker, expectation)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl StdError for Mismatch {}
+
+/// The error returned by `Harness::check`.
+#[derive(Debug)]
+pub enum CheckError<'a> {
+    /// The annotations of the test file could not be parsed.
+    InvalidAnnotations(Error<'a>),
+    /// The diagnostics did not match the annotations.
+    Mismatch(Mismatch),
+}
+
+impl fmt::Display for CheckError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::InvalidAnnotations(error) => write!(f, "invalid annotations:\n{}", error),
+            CheckError::Mismatch(mismatch) => write!(f, "{}", mismatch),
+        }
+    }
+}
+
+impl StdError for CheckError<'_> {}
+
+/// Checks the diagnostics produced for test files against their annotations.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{error::Error, source::Source, span::Span, testing::Harness};
+///
+/// let source = Source::from_str("test.rn", "let x = 10 //~ ERROR expected `;`\n");
+///
+/// Harness::new().assert(&source, |source| {
+///     vec![Error::new("expected `;`".to_string(), source, Span::empty(10))]
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Harness {
+    /// The line comment marker of the tested language.
+    comment: String,
+}
+
+impl Harness {
+    /// Creates a new `Harness` for languages with `//` line comments.
+    pub fn new() -> Self {
+        Harness {
+            comment: "//".to_string(),
+        }
+    }
+
+    /// Creates a new `Harness`, changing the line comment marker, such as `#` or `--`.
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = comment.to_string();
+        self
+    }
+
+    /// Parses the annotations of the given source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an annotation has an unknown kind, or refers to a line before the first one.
+    pub fn annotations<'a>(&self, source: &'a Source<'a>) -> Result<Vec<Expectation>, Error<'a>> {
+        let marker = format!("{}~", self.comment);
+        let mut annotations: Vec<Expectation> = Vec::new();
+        let mut offset = 0;
+
+        for (index, line) in source.code.split_inclusive('\n').enumerate() {
+            let line_start = offset;
+            offset += line.len();
+
+            let Some(position) = line.find(&marker) else {
+                continue;
+            };
+            let start = line_start + position;
+            let rest = &line[position + marker.len()..];
+
+            let (target, rest) = if let Some(rest) = rest.strip_prefix('|') {
+                match annotations.last() {
+                    Some(previous) => (Some(previous.line), rest),
+                    None => (None, rest),
+                }
+            } else {
+                let carets = rest.chars().take_while(|c| *c == '^').count();
+                (
+                    (index + 1).checked_sub(carets).filter(|line| *line > 0),
+                    &rest[carets..],
+                )
+            };
+
+            let annotation_span = Span::new(start, line_start + line.trim_end().len());
+            let Some(target) = target else {
+                return Err(Error::new(
+                    "annotation does not refer to a line".to_string(),
+                    source,
+                    annotation_span,
+                ));
+            };
+
+            let rest = rest.trim();
+            let (kind, message) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let Some(severity) = parse_severity(kind) else {
+                return Err(Error::new(
+                    format!("unknown annotation kind `{}`", kind),
+                    source,
+                    annotation_span,
+                )
+                .with_help(
+                    "expected one of `ERROR`, `WARNING`, `INFO`, `NOTE` or `HELP`".to_string(),
+                ));
+            };
+
+            annotations.push(Expectation {
+                line: target,
+                severity,
+                message: message.trim().to_string(),
+            });
+        }
+
+        Ok(annotations)
+    }
+
+    /// Runs the pipeline over the source and checks the diagnostics it produces against the annotations.
+    ///
+    /// A diagnostic matches an annotation on the line of its primary label with the same severity,
+    /// if the message of the annotation is part of the message of the diagnostic. Annotations with
+    /// the exact message are matched first and annotations without a message last, so that a less
+    /// specific annotation does not take a diagnostic a more specific one on the same line needs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CheckError::InvalidAnnotations` without running the pipeline if the annotations
+    /// cannot be parsed, and `CheckError::Mismatch` if the diagnostics do not match them.
+    pub fn check<'a, I>(
+        &self,
+        source: &'a Source<'a>,
+        pipeline: impl FnOnce(&'a Source<'a>) -> I,
+    ) -> Result<(), CheckError<'a>>
+    where
+        I: IntoIterator<Item = Error<'a>>,
+    {
+        let mut missing = self
+            .annotations(source)
+            .map_err(CheckError::InvalidAnnotations)?;
+
+        let mut actual = pipeline(source)
+            .into_iter()
+            .map(|error| Expectation {
+                line: location_to_line_col(&source.code, error.span().start).0,
+                severity: error.severity(),
+                message: error.message().to_string(),
+            })
+            .collect::<Vec<Expectation>>();
+        actual.sort_by_key(|expectation| expectation.line);
+
+        let passes: [fn(&Expectation, &Expectation) -> bool; 3] = [
+            |expectation, diagnostic| expectation.message == diagnostic.message,
+            |expectation, diagnostic| {
+                !expectation.message.is_empty() && diagnostic.message.contains(&expectation.message)
+            },
+            |expectation, _| expectation.message.is_empty(),
+        ];
+
+        let mut matched = Vec::new();
+        let mut unexpected = actual;
+
+        for matches in passes {
+            let mut unmatched = Vec::new();
+            for diagnostic in unexpected {
+                let position = missing.iter().position(|expectation| {
+                    expectation.line == diagnostic.line
+                        && expectation.severity == diagnostic.severity
+                        && matches(expectation, &diagnostic)
+                });
+
+                match position {
+                    Some(position) => matched.push(missing.remove(position)),
+                    None => unmatched.push(diagnostic),
+                }
+            }
+            unexpected = unmatched;
+        }
+
+        if missing.is_empty() && unexpected.is_empty() {
+            Ok(())
+        } else {
+            Err(CheckError::Mismatch(Mismatch {
+                filename: source.filename.to_string(),
+                matched,
+                missing,
+                unexpected,
+            }))
+        }
+    }
+
+    /// Runs the pipeline over the source, panicking with a diff if the diagnostics do not match the annotations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the annotations cannot be parsed, or if the diagnostics do not match them.
+    pub fn assert<'a, I>(&self, source: &'a Source<'a>, pipeline: impl FnOnce(&'a Source<'a>) -> I)
+    where
+        I: IntoIterator<Item = Error<'a>>,
+    {
+        if let Err(error) = self.check(source, pipeline) {
+            panic!("{}", error);
+        }
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Harness::new()
+    }
+}
+
+/// Parses the kind of an annotation, such as `ERROR`.
+fn parse_severity(kind: &str) -> Option<Severity> {
+    match kind {
+        "ERROR" => Some(Severity::Error),
+        "WARNING" | "WARN" => Some(Severity::Warning),
+        "INFO" => Some(Severity::Info),
+        "NOTE" => Some(Severity::Note),
+        "HELP" => Some(Severity::Help),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotations() {
+        let source = Source::from_str(
+            "test.rn",
+            "let x = 10 //~ ERROR expected `;`\nlet y = z\n//~^ ERROR unknown name\n//~| WARN\n",
+        );
+        let annotations = Harness::new().annotations(&source).unwrap();
+
+        assert_eq!(
+            annotations,
+            vec![
+                Expectation {
+                    line: 1,
+                    severity: Severity::Error,
+                    message: "expected `;`".to_string(),
+                },
+                Expectation {
+                    line: 2,
+                    severity: Severity::Error,
+                    message: "unknown name".to_string(),
+                },
+                Expectation {
+                    line: 2,
+                    severity: Severity::Warning,
+                    message: String::new(),
+                },
+            ]
+        );
+
+        let source = Source::from_str("test.rn", "//~^ ERROR too high\n");
+        assert!(Harness::new().annotations(&source).is_err());
+
+        let source = Source::from_str("test.rn", "# ~ nothing\nx #~ OOPS\n");
+        let error = Harness::new()
+            .with_comment("#")
+            .annotations(&source)
+            .unwrap_err();
+        assert_eq!(error.message(), "unknown annotation kind `OOPS`");
+    }
+
+    #[test]
+    fn test_check_annotations_on_one_line() {
+        let source = Source::from_str("test.rn", "let 1 = 2 //~ ERROR\n//~| ERROR expected name\n");
+
+        Harness::new().assert(&source, |source| {
+            vec![
+                Error::new("expected name".to_string(), source, Span::new(4, 5)),
+                Error::new("unexpected `=`".to_string(), source, Span::new(6, 7)),
+            ]
+        });
+    }
+
+    #[test]
+    fn test_check_mismatch() {
+        let source = Source::from_str(
+            "test.rn",
+            "let x = 10 //~ ERROR expected `;`\nlet y = z //~ ERROR unknown name\n",
+        );
+
+        let result = Harness::new().check(&source, |source| {
+            vec![
+                Error::new("expected `;`".to_string(), source, Span::empty(10)),
+                Error::new("unused variable `y`".to_string(), source, Span::new(38, 39))
+                    .with_severity(Severity::Warning),
+            ]
+        });
+        let Err(CheckError::Mismatch(mismatch)) = result else {
+            panic!("expected a mismatch");
+        };
+
+        assert_eq!(
+            mismatch.to_string(),
+            "diagnostics did not match the annotations in `test.rn`:\n  1: ERROR expected `;`\n- 2: ERROR unknown name\n+ 2: WARNING unused variable `y`\n"
+        );
+    }
+    #[test]
+    fn test_check_invalid_annotations() {
+        let source = Source::from_str("test.rn", "let x = 10 //~ OOPS expected `;`\n");
+
+        let result = Harness::new().check(&source, |_| -> Vec<Error> {
+            panic!("the pipeline must not run");
+        });
+        let Err(CheckError::InvalidAnnotations(error)) = result else {
+            panic!("expected invalid annotations");
+        };
+        assert_eq!(error.message(), "unknown annotation kind `OOPS`");
+    }
+}
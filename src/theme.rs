@@ -0,0 +1,213 @@
+//! This module defines the `Theme` struct, which controls the colors and glyphs of rendered diagnostics.
+//!
+//! The default theme matches the classic output: ASCII glyphs with colors suited to dark terminals.
+
+use colored::Color;
+
+use crate::{error::Severity, render::Style};
+
+/// The styles used for each part of a rendered diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Styles {
+    /// The style of errors.
+    pub error: Style,
+    /// The style of warnings.
+    pub warning: Style,
+    /// The style of informational diagnostics.
+    pub info: Style,
+    /// The style of notes.
+    pub note: Style,
+    /// The style of help messages.
+    pub help: Style,
+    /// The style of the gutter, arrows, line numbers and secondary labels.
+    pub accent: Style,
+    /// The style of messages and `note:`-like prefixes.
+    pub emphasis: Style,
+}
+
+/// The glyphs used to draw a rendered diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    /// The arrow before the location of the diagnostic.
+    pub arrow: &'static str,
+    /// The arrow before the location of an expansion frame.
+    pub expansion_arrow: &'static str,
+    /// The separator between the line numbers and the source code.
+    pub gutter: &'static str,
+    /// The marker before footers such as notes.
+    pub footer: &'static str,
+    /// The marker for elided lines and trimmed text.
+    pub ellipsis: &'static str,
+    /// The underline of primary labels.
+    pub primary: &'static str,
+    /// The underline of secondary labels and removed text.
+    pub secondary: &'static str,
+    /// The connector between an underline and a message printed below it.
+    pub connector: &'static str,
+    /// The marker for text inserted by a suggestion.
+    pub insertion: &'static str,
+    /// The marker for text replaced by a suggestion.
+    pub replacement: &'static str,
+}
+
+impl Glyphs {
+    /// Returns the ASCII glyphs, such as `-->`, `|` and `^`.
+    pub const fn ascii() -> Self {
+        Glyphs {
+            arrow: "-->",
+            expansion_arrow: ":::",
+            gutter: "|",
+            footer: "=",
+            ellipsis: "...",
+            primary: "^",
+            secondary: "-",
+            connector: "|",
+            insertion: "+",
+            replacement: "~",
+        }
+    }
+
+    /// Returns Unicode box-drawing glyphs, such as `╭─▶`, `│` and `━`.
+    pub const fn unicode() -> Self {
+        Glyphs {
+            arrow: "╭─▶",
+            expansion_arrow: "├─▶",
+            gutter: "│",
+            footer: "•",
+            ellipsis: "…",
+            primary: "━",
+            secondary: "─",
+            connector: "│",
+            insertion: "+",
+            replacement: "~",
+        }
+    }
+}
+
+/// Controls the colors and glyphs of rendered diagnostics.
+///
+/// Colors are only used if the `RenderConfig` enables them.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{
+///     render::RenderConfig,
+///     theme::{Glyphs, Theme},
+/// };
+///
+/// let config = RenderConfig {
+///     theme: Theme::light().with_glyphs(Glyphs::unicode()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The styles of the diagnostic.
+    pub styles: Styles,
+    /// The glyphs of the diagnostic.
+    pub glyphs: Glyphs,
+    /// Whether line numbers are shown in the gutter.
+    pub line_numbers: bool,
+}
+
+impl Theme {
+    /// Returns a theme with colors suited to dark terminals and ASCII glyphs.
+    pub const fn dark() -> Self {
+        Theme {
+            styles: Styles {
+                error: Style::new().color(Color::Red).bold(),
+                warning: Style::new().color(Color::Yellow).bold(),
+                info: Style::new().color(Color::Blue).bold(),
+                note: Style::new().color(Color::Green).bold(),
+                help: Style::new().color(Color::Cyan).bold(),
+                accent: Style::new().color(Color::Cyan).bold(),
+                emphasis: Style::new().bold(),
+            },
+            glyphs: Glyphs::ascii(),
+            line_numbers: true,
+        }
+    }
+
+    /// Returns a theme with colors suited to light terminals and ASCII glyphs.
+    pub const fn light() -> Self {
+        Theme {
+            styles: Styles {
+                error: Style::new().color(Color::Red).bold(),
+                warning: Style::new().color(Color::Magenta).bold(),
+                info: Style::new().color(Color::Blue).bold(),
+                note: Style::new().color(Color::Green).bold(),
+                help: Style::new().color(Color::Blue).bold(),
+                accent: Style::new().color(Color::Blue).bold(),
+                emphasis: Style::new().bold(),
+            },
+            glyphs: Glyphs::ascii(),
+            line_numbers: true,
+        }
+    }
+
+    /// Returns a theme without any colors or weights and with ASCII glyphs.
+    pub const fn ascii() -> Self {
+        Theme {
+            styles: Styles {
+                error: Style::new(),
+                warning: Style::new(),
+                info: Style::new(),
+                note: Style::new(),
+                help: Style::new(),
+                accent: Style::new(),
+                emphasis: Style::new(),
+            },
+            glyphs: Glyphs::ascii(),
+            line_numbers: true,
+        }
+    }
+
+    /// Returns the theme with the given glyphs.
+    pub const fn with_glyphs(mut self, glyphs: Glyphs) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Returns the theme with line numbers shown or hidden.
+    pub const fn with_line_numbers(mut self, enabled: bool) -> Self {
+        self.line_numbers = enabled;
+        self
+    }
+
+    /// Returns the style of the given severity.
+    pub fn severity(&self, severity: Severity) -> Style {
+        match severity {
+            Severity::Error => self.styles.error,
+            Severity::Warning => self.styles.warning,
+            Severity::Info => self.styles.info,
+            Severity::Note => self.styles.note,
+            Severity::Help => self.styles.help,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_presets() {
+        assert_eq!(Theme::default(), Theme::dark());
+        assert_eq!(
+            Theme::dark().severity(Severity::Error),
+            Style::new().color(Color::Red).bold()
+        );
+        assert_eq!(Theme::ascii().severity(Severity::Warning), Style::new());
+        assert_eq!(
+            Theme::light().with_glyphs(Glyphs::unicode()).glyphs.gutter,
+            "│"
+        );
+    }
+}
@@ -1,9 +1,133 @@
 //! This module defines the `Token` struct, which represents a token in the source code.
+//! It also defines the `TokenKind` trait, which lets generic parser utilities compare and name token kinds.
 
-use crate::span::Span;
+use std::mem;
+
+use crate::{
+    source::Source,
+    span::{HasSpan, Span},
+};
+
+/// The broad category of a token kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenCategory {
+    /// A reserved word, such as `let`.
+    Keyword,
+    /// A name, such as a variable name.
+    Identifier,
+    /// A literal value, such as a number or a string.
+    Literal,
+    /// An operator or punctuation, such as `+` or `;`.
+    Operator,
+    /// Text without meaning to the parser, such as whitespace and comments.
+    Trivia,
+}
+
+/// Defines the interface for token kinds.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::token::{TokenCategory, TokenKind};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Kind {
+///     Let,
+///     Ident(String),
+/// }
+///
+/// impl TokenKind for Kind {
+///     fn describe(&self) -> String {
+///         match self {
+///             Kind::Let => "`let`".to_string(),
+///             Kind::Ident(_) => "identifier".to_string(),
+///         }
+///     }
+///
+///     fn category(&self) -> Option<TokenCategory> {
+///         match self {
+///             Kind::Let => Some(TokenCategory::Keyword),
+///             Kind::Ident(_) => Some(TokenCategory::Identifier),
+///         }
+///     }
+/// }
+///
+/// assert!(Kind::Ident("x".to_string()).same_kind(&Kind::Ident(String::new())));
+/// assert!(!Kind::Let.same_kind(&Kind::Ident(String::new())));
+/// ```
+pub trait TokenKind {
+    /// Returns `true` if both kinds are the same, ignoring their payloads.
+    ///
+    /// The default implementation compares enum discriminants, so `Ident("x")` and `Ident("y")`
+    /// are the same kind. Types that are not enums should override it.
+    fn same_kind(&self, other: &Self) -> bool
+    where
+        Self: Sized,
+    {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+
+    /// Returns a human-readable description of the kind, such as `` `let` `` or `identifier`,
+    /// used in messages such as "expected one of ...".
+    fn describe(&self) -> String;
+
+    /// Returns the category of the kind, if any.
+    fn category(&self) -> Option<TokenCategory> {
+        None
+    }
+
+    /// Returns `true` if the kind is trivia, such as whitespace or a comment.
+    fn is_trivia(&self) -> bool {
+        self.category() == Some(TokenCategory::Trivia)
+    }
+}
+
+impl TokenKind for &str {
+    fn same_kind(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn describe(&self) -> String {
+        format!("`{}`", self)
+    }
+}
+
+impl TokenKind for String {
+    fn same_kind(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn describe(&self) -> String {
+        format!("`{}`", self)
+    }
+}
+
+/// Describes a list of expected kinds, such as `` `;` ``, `` `;` or `}` `` or `` one of `,`, `;` or `}` ``.
+///
+/// Kinds with the same description are only listed once.
+pub fn describe_expected<T: TokenKind>(kinds: &[T]) -> String {
+    describe_alternatives(kinds.iter().map(TokenKind::describe))
+}
+
+/// Joins descriptions of expected items, such as `` one of `,`, `;` or `}` ``, skipping duplicates.
+pub(crate) fn describe_alternatives(descriptions: impl IntoIterator<Item = String>) -> String {
+    let mut unique: Vec<String> = Vec::new();
+    for description in descriptions {
+        if !unique.contains(&description) {
+            unique.push(description);
+        }
+    }
+
+    match unique.as_slice() {
+        [] => "nothing".to_string(),
+        [only] => only.clone(),
+        [first, second] => format!("{} or {}", first, second),
+        [rest @ .., last] => format!("one of {} or {}", rest.join(", "), last),
+    }
+}
 
 /// Represents a token in the source code.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Token<T> {
     /// The kind of token
     pub kind: T,
@@ -16,6 +140,21 @@ impl<T> Token<T> {
     pub fn new(kind: T, span: Span) -> Self {
         Token { kind, span }
     }
+
+    /// Returns the text of the token in the given source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span of the token is out of bounds for the source.
+    pub fn text<'s>(&self, source: &'s Source) -> &'s str {
+        self.span.text(source)
+    }
+}
+
+impl<T> HasSpan for Token<T> {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 #[cfg(test)]
@@ -31,5 +170,57 @@ mod tests {
         assert_eq!(token.kind, "let");
         assert_eq!(token.span.start, 0);
         assert_eq!(token.span.end, 10);
+        assert_eq!(token.span(), Span::new(0, 10));
+        assert_eq!(token.clone(), token);
+
+        let source = Source::from_str("test.rs", "let x = 10;");
+        assert_eq!(Token::new("ident", Span::new(4, 5)).text(&source), "x");
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Kind {
+        Number(u32),
+        Plus,
+        Comment,
+    }
+
+    impl TokenKind for Kind {
+        fn describe(&self) -> String {
+            match self {
+                Kind::Number(_) => "number".to_string(),
+                Kind::Plus => "`+`".to_string(),
+                Kind::Comment => "comment".to_string(),
+            }
+        }
+
+        fn category(&self) -> Option<TokenCategory> {
+            match self {
+                Kind::Number(_) => Some(TokenCategory::Literal),
+                Kind::Plus => Some(TokenCategory::Operator),
+                Kind::Comment => Some(TokenCategory::Trivia),
+            }
+        }
+    }
+
+    #[test]
+    fn test_token_kind() {
+        assert!(Kind::Number(1).same_kind(&Kind::Number(2)));
+        assert!(!Kind::Number(1).same_kind(&Kind::Plus));
+        assert!(Kind::Comment.is_trivia());
+        assert!(!"let".same_kind(&"var"));
+    }
+
+    #[test]
+    fn test_describe_expected() {
+        assert_eq!(describe_expected::<Kind>(&[]), "nothing");
+        assert_eq!(describe_expected(&[Kind::Plus]), "`+`");
+        assert_eq!(
+            describe_expected(&[Kind::Number(0), Kind::Number(1), Kind::Plus]),
+            "number or `+`"
+        );
+        assert_eq!(
+            describe_expected(&[";", ",", "}"]),
+            "one of `;`, `,` or `}`"
+        );
     }
 }
@@ -0,0 +1,240 @@
+//! This module groups a flat list of tokens into token trees, by matching delimiters.
+//!
+//! Macro systems and error recovery work on token trees, where each pair of delimiters such as
+//! `(` and `)` and the tokens between them form a single `Group`.
+
+use crate::{
+    error::Error,
+    source::Source,
+    span::{HasSpan, Span},
+    token::{Token, TokenKind},
+};
+
+/// A token, or a group of tokens between matching delimiters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TokenTree<T> {
+    /// A token which is not a delimiter.
+    Leaf(Token<T>),
+    /// Tokens between matching delimiters.
+    Group(Group<T>),
+}
+
+impl<T> HasSpan for TokenTree<T> {
+    fn span(&self) -> Span {
+        match self {
+            TokenTree::Leaf(token) => token.span,
+            TokenTree::Group(group) => group.span(),
+        }
+    }
+}
+
+/// Tokens between matching delimiters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Group<T> {
+    /// The opening delimiter.
+    pub open: Token<T>,
+    /// The token trees between the delimiters.
+    pub trees: Vec<TokenTree<T>>,
+    /// The closing delimiter.
+    pub close: Token<T>,
+}
+
+impl<T> Group<T> {
+    /// Returns the span between the delimiters, excluding them.
+    pub fn inner_span(&self) -> Span {
+        Span::new(self.open.span.end, self.close.span.start)
+    }
+}
+
+impl<T> HasSpan for Group<T> {
+    fn span(&self) -> Span {
+        self.open.span.to(self.close.span)
+    }
+}
+
+/// The pairs of delimiters used to build token trees.
+///
+/// # Usage
+///
+/// ```rust
+/// use runic_kit::{source::Source, span::Span, token::Token, token_tree::{Delimiters, TokenTree}};
+///
+/// let source = Source::from_str("main.rn", "f ( x )");
+/// let tokens = vec![
+///     Token::new("f", Span::new(0, 1)),
+///     Token::new("(", Span::new(2, 3)),
+///     Token::new("x", Span::new(4, 5)),
+///     Token::new(")", Span::new(6, 7)),
+/// ];
+///
+/// let delimiters = Delimiters::new().with_pair("(", ")");
+/// let trees = delimiters.build(&source, tokens).unwrap();
+///
+/// assert_eq!(trees.len(), 2);
+/// let TokenTree::Group(group) = &trees[1] else { panic!() };
+/// assert_eq!(group.trees.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Delimiters<T> {
+    /// The pairs of opening and closing delimiters.
+    pairs: Vec<(T, T)>,
+}
+
+impl<T: TokenKind> Delimiters<T> {
+    /// Creates a new `Delimiters` without any pairs.
+    pub fn new() -> Self {
+        Delimiters { pairs: Vec::new() }
+    }
+
+    /// Creates a new `Delimiters`, adding a pair of delimiters, such as `(` and `)`.
+    pub fn with_pair(mut self, open: T, close: T) -> Self {
+        self.pairs.push((open, close));
+        self
+    }
+
+    /// Groups the tokens into token trees.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first unbalanced delimiter: a closing delimiter without an
+    /// opening one, a closing delimiter of the wrong kind, which also points at the unclosed
+    /// opening one, or an opening delimiter left unclosed at the end of the file.
+    pub fn build<'a>(
+        &self,
+        source: &'a Source<'a>,
+        tokens: Vec<Token<T>>,
+    ) -> Result<Vec<TokenTree<T>>, Error<'a>> {
+        let mut trees = Vec::new();
+        let mut open: Vec<(Token<T>, Vec<TokenTree<T>>)> = Vec::new();
+
+        for token in tokens {
+            if self.is_open(&token.kind) {
+                open.push((token, std::mem::take(&mut trees)));
+                continue;
+            }
+
+            if !self.is_close(&token.kind) {
+                trees.push(TokenTree::Leaf(token));
+                continue;
+            }
+
+            let Some((opener, outer)) = open.pop() else {
+                return Err(Error::new(
+                    format!("unexpected closing delimiter: {}", token.kind.describe()),
+                    source,
+                    token.span,
+                )
+                .with_primary_label("unexpected closing delimiter".to_string()));
+            };
+
+            if !self.matches(&opener.kind, &token.kind) {
+                return Err(Error::new(
+                    format!("mismatched closing delimiter: {}", token.kind.describe()),
+                    source,
+                    token.span,
+                )
+                .with_primary_label("mismatched closing delimiter".to_string())
+                .with_label(opener.span, "unclosed delimiter".to_string()));
+            }
+
+            let group = Group {
+                open: opener,
+                trees: std::mem::replace(&mut trees, outer),
+                close: token,
+            };
+            trees.push(TokenTree::Group(group));
+        }
+
+        match open.pop() {
+            Some((opener, _)) => Err(Error::new(
+                "this file contains an unclosed delimiter".to_string(),
+                source,
+                Span::empty(source.code.len()),
+            )
+            .with_label(opener.span, "unclosed delimiter".to_string())),
+            None => Ok(trees),
+        }
+    }
+
+    /// Returns `true` if the kind is an opening delimiter.
+    fn is_open(&self, kind: &T) -> bool {
+        self.pairs.iter().any(|(open, _)| kind.same_kind(open))
+    }
+
+    /// Returns `true` if the kind is a closing delimiter.
+    fn is_close(&self, kind: &T) -> bool {
+        self.pairs.iter().any(|(_, close)| kind.same_kind(close))
+    }
+
+    /// Returns `true` if the opening and closing delimiters form a pair.
+    fn matches(&self, open: &T, close: &T) -> bool {
+        self.pairs
+            .iter()
+            .any(|(o, c)| open.same_kind(o) && close.same_kind(c))
+    }
+}
+
+impl<T: TokenKind> Default for Delimiters<T> {
+    fn default() -> Self {
+        Delimiters::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::LabelStyle, test_utils::tokenize};
+
+    fn delimiters() -> Delimiters<&'static str> {
+        Delimiters::new()
+            .with_pair("(", ")")
+            .with_pair("[", "]")
+            .with_pair("{", "}")
+    }
+
+    #[test]
+    fn test_build_nested_groups() {
+        let source = Source::from_str("test.rn", "a ( b [ c ] { } ) d");
+        let trees = delimiters().build(&source, tokenize(&source.code)).unwrap();
+
+        assert_eq!(trees.len(), 3);
+        let TokenTree::Group(group) = &trees[1] else {
+            panic!("expected a group");
+        };
+        assert_eq!(group.span(), Span::new(2, 17));
+        assert_eq!(group.inner_span(), Span::new(3, 16));
+        assert_eq!(group.trees.len(), 3);
+        assert_eq!(group.trees[1].span(), Span::new(6, 11));
+        assert!(matches!(&group.trees[2], TokenTree::Group(inner) if inner.trees.is_empty()));
+        assert_eq!(
+            trees[2],
+            TokenTree::Leaf(Token::new("d", Span::new(18, 19)))
+        );
+    }
+
+    #[test]
+    fn test_build_unbalanced_delimiters() {
+        let source = Source::from_str("test.rn", "( [ ) ]");
+        let error = delimiters()
+            .build(&source, tokenize(&source.code))
+            .unwrap_err();
+        assert_eq!(error.message(), "mismatched closing delimiter: `)`");
+        assert_eq!(error.span(), Span::new(4, 5));
+        assert_eq!(error.labels()[1].span, Span::new(2, 3));
+        assert_eq!(error.labels()[1].style, LabelStyle::Secondary);
+
+        let source = Source::from_str("test.rn", "a )");
+        let error = delimiters()
+            .build(&source, tokenize(&source.code))
+            .unwrap_err();
+        assert_eq!(error.message(), "unexpected closing delimiter: `)`");
+
+        let source = Source::from_str("test.rn", "{ ( )");
+        let error = delimiters()
+            .build(&source, tokenize(&source.code))
+            .unwrap_err();
+        assert_eq!(error.message(), "this file contains an unclosed delimiter");
+        assert_eq!(error.span(), Span::empty(5));
+        assert_eq!(error.labels()[1].span, Span::new(0, 1));
+    }
+}